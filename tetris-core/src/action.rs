@@ -0,0 +1,112 @@
+use crate::Tetromino;
+
+/// A command that a solution can give to a tetromino before it falls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum Action {
+    #[display(fmt = "shift_left")]
+    ShiftLeft,
+    #[display(fmt = "shift_right")]
+    ShiftRight,
+    #[display(fmt = "rotate")]
+    Rotate,
+    /// Moves the tetromino one line down (only with the step-by-step gravity, see
+    /// [`crate::FallingTetromino`]).
+    #[display(fmt = "down")]
+    Down,
+    /// Puts the tetromino aside and brings back the previously held one (it is handled by the
+    /// game, and it does not move the tetromino).
+    #[display(fmt = "hold")]
+    Hold,
+}
+
+impl std::str::FromStr for Action {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "shift_left" => Self::ShiftLeft,
+            "shift_right" => Self::ShiftRight,
+            "rotate" => Self::Rotate,
+            "down" => Self::Down,
+            "hold" => Self::Hold,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl Tetromino {
+    /// Moves the tetromino within the board of the given width. Shifts into the walls are
+    /// ignored, and a rotation next to the right wall moves the tetromino to the left, so the
+    /// tetromino always stays within the board.
+    pub fn apply_action(&mut self, action: Action, board_width: usize) {
+        match action {
+            Action::ShiftLeft => {
+                if self.position > 1 {
+                    self.position -= 1;
+                }
+            }
+            Action::ShiftRight => {
+                if self.position + self.width() <= board_width {
+                    self.position += 1;
+                }
+            }
+            Action::Rotate => {
+                self.rotation += 90;
+                // Allow rotating tetromino when it does not fit by just updating the position
+                let rightmost_allowed_position = board_width - self.width() + 1;
+                if self.position > rightmost_allowed_position {
+                    self.position = rightmost_allowed_position;
+                }
+            }
+            Action::Down | Action::Hold => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TetrominoKind;
+
+    #[test]
+    fn test_apply_action() {
+        let mut tetromino = Tetromino {
+            kind: TetrominoKind::I,
+            position: 2,
+            rotation: 0,
+        };
+        for &(action, position, rotation) in &[
+            (Action::ShiftLeft, 1, 0),
+            (Action::ShiftLeft, 1, 0),
+            (Action::Rotate, 1, 90),
+            (Action::ShiftRight, 2, 90),
+            (Action::ShiftRight, 3, 90),
+            (Action::ShiftRight, 3, 90),
+            (Action::Rotate, 3, 180),
+            (Action::ShiftRight, 4, 180),
+            (Action::ShiftRight, 5, 180),
+            (Action::ShiftRight, 6, 180),
+            (Action::Rotate, 3, 270),
+        ] {
+            tetromino.apply_action(action, 6);
+            assert_eq!(
+                (tetromino.position, tetromino.rotation),
+                (position, rotation)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_action() {
+        for &action in &[
+            Action::ShiftLeft,
+            Action::ShiftRight,
+            Action::Rotate,
+            Action::Down,
+            Action::Hold,
+        ] {
+            assert_eq!(action.to_string().parse(), Ok(action));
+        }
+        assert_eq!("drop".parse::<Action>(), Err(()));
+    }
+}
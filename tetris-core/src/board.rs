@@ -0,0 +1,472 @@
+use tracing::{debug, trace};
+
+use crate::Tetromino;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TetrisCell {
+    #[default]
+    Empty,
+    Occupied,
+}
+
+impl TetrisCell {
+    pub fn is_occupied(&self) -> bool {
+        matches!(self, TetrisCell::Occupied)
+    }
+}
+
+/// The tetromino cannot be placed on the board: it either sticks out of the board or overlaps
+/// with the occupied cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+#[display(fmt = "tetromino does not fit on the board")]
+pub struct DoesNotFit;
+
+pub type TetrisBoardLine = Vec<TetrisCell>;
+
+/// The starting lines of the board (e.g. from a test) do not make a valid board.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum BoardError {
+    #[display(
+        fmt = "invalid row {:?} (expected `#` and spaces between `|` and `|`)",
+        _0
+    )]
+    InvalidRow(String),
+    #[display(fmt = "the row {:?} is not {} cells wide", _0, _1)]
+    InvalidWidth(String, usize),
+    #[display(fmt = "{} rows do not fit into the board of the height {}", _0, _1)]
+    TooManyRows(usize, usize),
+    #[display(fmt = "the row {:?} is full", _0)]
+    FullRow(String),
+}
+
+/// The game board, where the lines are stored from the bottom to the top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TetrisBoard(Vec<TetrisBoardLine>);
+
+impl Default for TetrisBoard {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_WIDTH, Self::DEFAULT_HEIGHT)
+    }
+}
+
+impl std::fmt::Display for TetrisBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in self.0.iter().rev() {
+            format!("{}\n", Self::format_line(line)).fmt(f)?;
+        }
+        "_".repeat(self.width() + 2).fmt(f)
+    }
+}
+
+impl TetrisBoard {
+    pub const DEFAULT_WIDTH: usize = 10;
+    pub const DEFAULT_HEIGHT: usize = 20;
+    /// Every tetromino in every rotation has to fit into an empty board.
+    pub const MIN_SIZE: usize = 4;
+
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(
+            width >= Self::MIN_SIZE && height >= Self::MIN_SIZE,
+            "The board {}x{} is too small",
+            width,
+            height
+        );
+        Self(vec![vec![TetrisCell::Empty; width]; height])
+    }
+
+    /// The board with the given lines at the bottom (listed from the bottom to the top).
+    pub fn with_lines(
+        width: usize,
+        height: usize,
+        lines: &[TetrisBoardLine],
+    ) -> Result<Self, BoardError> {
+        let mut board = Self::new(width, height);
+        if lines.len() > height {
+            return Err(BoardError::TooManyRows(lines.len(), height));
+        }
+        for (board_line, line) in board.0.iter_mut().zip(lines) {
+            if line.len() != width {
+                return Err(BoardError::InvalidWidth(Self::format_line(line), width));
+            }
+            // A full line would be cleared before the game even starts
+            if line.iter().all(TetrisCell::is_occupied) {
+                return Err(BoardError::FullRow(Self::format_line(line)));
+            }
+            board_line.clone_from(line);
+        }
+        Ok(board)
+    }
+
+    /// Parses the rows in the format of the board display, e.g. `|#  # |`, from the top to the
+    /// bottom, and returns the lines from the bottom to the top.
+    pub fn parse_lines<'a>(
+        rows: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<TetrisBoardLine>, BoardError> {
+        let mut lines = rows
+            .into_iter()
+            .map(|row| {
+                row.strip_prefix('|')
+                    .and_then(|row| row.strip_suffix('|'))
+                    .and_then(|cells| {
+                        cells
+                            .chars()
+                            .map(|cell| match cell {
+                                ' ' => Some(TetrisCell::Empty),
+                                '#' => Some(TetrisCell::Occupied),
+                                _ => None,
+                            })
+                            .collect::<Option<TetrisBoardLine>>()
+                    })
+                    .ok_or_else(|| BoardError::InvalidRow(row.to_owned()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        lines.reverse();
+        Ok(lines)
+    }
+
+    /// The line in the format of the board display, e.g. `|#  # |`.
+    pub fn format_line(line: &[TetrisCell]) -> String {
+        let cells = line
+            .iter()
+            .map(|cell| match cell {
+                TetrisCell::Empty => ' ',
+                TetrisCell::Occupied => '#',
+            })
+            .collect::<String>();
+        format!("|{}|", cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.0[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn lines(&self) -> &[TetrisBoardLine] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0
+            .iter()
+            .all(|line| line.iter().all(|cell| !cell.is_occupied()))
+    }
+
+    /// Checks if the tetromino fits into the board when its top line is placed on the topmost
+    /// line of the given (sub-)board, and marks its blocks as occupied if `should_save` is set.
+    pub fn fit_tetromino(
+        board: &mut [TetrisBoardLine],
+        tetromino: &Tetromino,
+        should_save: bool,
+    ) -> Result<(), DoesNotFit> {
+        let cells = Self::tetromino_cells(board, tetromino)?;
+        if should_save {
+            for (column, line) in cells {
+                board[line][column] = TetrisCell::Occupied;
+            }
+        }
+        Ok(())
+    }
+
+    /// The `(column, line)` cells that the tetromino takes when its top line is placed on the
+    /// topmost line of the given (sub-)board, if they are all within the board and empty.
+    fn tetromino_cells(
+        board: &[TetrisBoardLine],
+        tetromino: &Tetromino,
+    ) -> Result<Vec<(usize, usize)>, DoesNotFit> {
+        if board.is_empty() {
+            return Err(DoesNotFit);
+        }
+        let board_top = board.len() - 1;
+        let cells = tetromino
+            .blocks()
+            .iter()
+            .map(|(x, y)| match (x + tetromino.position).checked_sub(1) {
+                Some(x) if x < board[0].len() && *y < board.len() => Ok((x, board_top - y)),
+                _ => Err(DoesNotFit),
+            })
+            .collect::<Result<Vec<(usize, usize)>, DoesNotFit>>()?;
+
+        if cells
+            .iter()
+            .any(|&(column, line)| board[line][column].is_occupied())
+        {
+            return Err(DoesNotFit);
+        }
+        Ok(cells)
+    }
+
+    /// Checks if the tetromino fits into the board when its top line is on the given line
+    /// (counted from the bottom, starting with 0).
+    pub fn fits(&self, tetromino: &Tetromino, top_line: usize) -> bool {
+        top_line < self.0.len() && Self::tetromino_cells(&self.0[..=top_line], tetromino).is_ok()
+    }
+
+    /// Marks the blocks of the tetromino as occupied when its top line is on the given line.
+    pub fn place_tetromino(
+        &mut self,
+        tetromino: &Tetromino,
+        top_line: usize,
+    ) -> Result<(), DoesNotFit> {
+        if top_line >= self.0.len() {
+            return Err(DoesNotFit);
+        }
+        Self::fit_tetromino(&mut self.0[..=top_line], tetromino, true)
+    }
+
+    /// Drops the tetromino straight down from the top of the board, and returns the line where
+    /// the top of the tetromino has landed (counted from the bottom, starting with 0).
+    pub fn try_apply_tetromino(&mut self, tetromino: Tetromino) -> Result<usize, DoesNotFit> {
+        trace!("Trying to apply {:?} to the board\n{}", tetromino, self);
+        let mut vertical_position = self.0.len();
+        while Self::fit_tetromino(&mut self.0[..vertical_position], &tetromino, false).is_ok() {
+            vertical_position -= 1;
+        }
+        if vertical_position == self.0.len() {
+            debug!("Tetromino could not get placed on the board");
+            Err(DoesNotFit)
+        } else {
+            debug!(
+                "Tetromino {} shifted by {} will get placed on the line {}",
+                tetromino.kind, tetromino.position, vertical_position
+            );
+            Self::fit_tetromino(&mut self.0[..vertical_position + 1], &tetromino, true)
+                .expect("unreachable");
+            trace!("Applied {:?} to the board\n{}", tetromino, self);
+
+            Ok(vertical_position)
+        }
+    }
+
+    /// Pushes a garbage row with a hole in the given column (starting with 0) into the bottom of
+    /// the board, which shifts the stack up. It fails when the stack is pushed out of the top
+    /// (the blocks of the top line are lost then).
+    pub fn raise_garbage(&mut self, hole: usize) -> Result<(), DoesNotFit> {
+        let mut garbage = vec![TetrisCell::Occupied; self.width()];
+        garbage[hole] = TetrisCell::Empty;
+        self.0.insert(0, garbage);
+        let top_line = self.0.pop().expect("the board has lines");
+        if top_line.iter().any(TetrisCell::is_occupied) {
+            debug!("The garbage has pushed the stack out of the board");
+            return Err(DoesNotFit);
+        }
+        trace!("Raised the garbage:\n{}", self);
+        Ok(())
+    }
+
+    /// Removes the fully occupied lines and returns the number of the removed lines.
+    pub fn clean_full_lines(&mut self) -> u64 {
+        let mut cleaned_lines = 0;
+        let mut line_index = 0;
+        while line_index < self.0.len() {
+            if self.0[line_index].iter().all(TetrisCell::is_occupied) {
+                self.0.remove(line_index);
+                self.0.push(vec![TetrisCell::Empty; self.width()]);
+                cleaned_lines += 1;
+            } else {
+                line_index += 1;
+            }
+        }
+
+        debug!("Cleaned {} lines.", cleaned_lines);
+        trace!("The board is:\n{}", self);
+        cleaned_lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use insta::assert_snapshot;
+
+    use super::*;
+    use crate::TetrominoKind;
+
+    #[test]
+    fn test_tetromino_blocks() {
+        for tetromino_kind in &TetrominoKind::ALL {
+            for &rotation in &[0, 90, 180, 270] {
+                let mut board = TetrisBoard::default();
+                board
+                    .try_apply_tetromino(Tetromino {
+                        kind: *tetromino_kind,
+                        position: 1,
+                        rotation,
+                    })
+                    .unwrap();
+                assert_snapshot!(
+                    format!("tetromino_blocks_{}_{}", tetromino_kind, rotation),
+                    board.to_string()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_tetromino_out_of_board() {
+        let mut board = TetrisBoard::default();
+        for &position in &[0, 8, 11] {
+            assert_eq!(
+                board.try_apply_tetromino(Tetromino {
+                    kind: TetrominoKind::I,
+                    position,
+                    rotation: 90,
+                }),
+                Err(DoesNotFit)
+            );
+        }
+    }
+
+    #[test]
+    fn test_tetris_board() {
+        let mut board = TetrisBoard::default();
+
+        board
+            .try_apply_tetromino(Tetromino {
+                kind: TetrominoKind::I,
+                position: 1,
+                rotation: 90,
+            })
+            .unwrap();
+        board.clean_full_lines();
+        assert_snapshot!(board.to_string());
+
+        board
+            .try_apply_tetromino(Tetromino {
+                kind: TetrominoKind::I,
+                position: 1,
+                rotation: 270,
+            })
+            .unwrap();
+        board.clean_full_lines();
+        assert_snapshot!(board.to_string());
+
+        board
+            .try_apply_tetromino(Tetromino {
+                kind: TetrominoKind::I,
+                position: 9,
+                rotation: 180,
+            })
+            .unwrap();
+        board.clean_full_lines();
+        assert_snapshot!(board.to_string());
+
+        board
+            .try_apply_tetromino(Tetromino {
+                kind: TetrominoKind::I,
+                position: 10,
+                rotation: 360,
+            })
+            .unwrap();
+        board.clean_full_lines();
+        assert_snapshot!(board.to_string());
+        board
+            .try_apply_tetromino(Tetromino {
+                kind: TetrominoKind::O,
+                position: 5,
+                rotation: 90,
+            })
+            .unwrap();
+        board.clean_full_lines();
+        assert_snapshot!(board.to_string());
+
+        board
+            .try_apply_tetromino(Tetromino {
+                kind: TetrominoKind::O,
+                position: 7,
+                rotation: 90,
+            })
+            .unwrap();
+        assert_snapshot!(board.to_string());
+        board.clean_full_lines();
+        assert_snapshot!(board.to_string());
+    }
+
+    #[test]
+    fn test_board_size() {
+        let mut board = TetrisBoard::new(4, 6);
+        assert_eq!((board.width(), board.height()), (4, 6));
+        for &(kind, position, rotation) in &[
+            (TetrominoKind::I, 1, 90),
+            (TetrominoKind::O, 1, 0),
+            (TetrominoKind::O, 3, 0),
+            (TetrominoKind::L, 2, 0),
+        ] {
+            board
+                .try_apply_tetromino(Tetromino {
+                    kind,
+                    position,
+                    rotation,
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            board.try_apply_tetromino(Tetromino {
+                kind: TetrominoKind::I,
+                position: 2,
+                rotation: 90,
+            }),
+            Err(DoesNotFit)
+        );
+        assert_snapshot!(board.to_string());
+        assert_eq!(board.clean_full_lines(), 3);
+        assert_snapshot!(board.to_string());
+        assert_eq!(board.lines().len(), 6);
+        assert!(!board.is_empty());
+        assert!(TetrisBoard::new(4, 6).is_empty());
+    }
+
+    #[test]
+    fn test_starting_lines() {
+        let lines = TetrisBoard::parse_lines(vec!["|  #  |", "|## ##|"]).unwrap();
+        assert_eq!(TetrisBoard::format_line(&lines[0]), "|## ##|");
+        let board = TetrisBoard::with_lines(5, 4, &lines).unwrap();
+        assert_eq!(
+            board.to_string(),
+            "|     |\n|     |\n|  #  |\n|## ##|\n_______"
+        );
+
+        assert_eq!(
+            TetrisBoard::parse_lines(vec!["|  #  |", "|##.##|"]),
+            Err(BoardError::InvalidRow("|##.##|".into()))
+        );
+        assert_eq!(
+            TetrisBoard::parse_lines(vec!["  #  |"]),
+            Err(BoardError::InvalidRow("  #  |".into()))
+        );
+        assert_eq!(
+            TetrisBoard::with_lines(4, 4, &lines),
+            Err(BoardError::InvalidWidth("|## ##|".into(), 4))
+        );
+        assert_eq!(
+            TetrisBoard::with_lines(5, 4, &vec![lines[0].clone(); 5]),
+            Err(BoardError::TooManyRows(5, 4))
+        );
+        let full_lines = TetrisBoard::parse_lines(vec!["|#####|"]).unwrap();
+        assert_eq!(
+            TetrisBoard::with_lines(5, 4, &full_lines),
+            Err(BoardError::FullRow("|#####|".into()))
+        );
+    }
+
+    #[test]
+    fn test_raise_garbage() {
+        let mut board = TetrisBoard::new(4, 4);
+        board
+            .try_apply_tetromino(Tetromino {
+                kind: TetrominoKind::O,
+                position: 1,
+                rotation: 0,
+            })
+            .unwrap();
+        board.raise_garbage(2).unwrap();
+        board.raise_garbage(0).unwrap();
+        assert_snapshot!(board.to_string());
+        // The tetromino is pushed out of the top
+        assert_eq!(board.raise_garbage(3), Err(DoesNotFit));
+        assert_eq!(board.lines().len(), 4);
+    }
+}
@@ -0,0 +1,8 @@
+//! Exit codes with which the interactor reports its verdict, the same as testlib's `TResult` so
+//! that the testlib-style checkers (e.g. DDOTS) understand them.
+
+pub const OK: i32 = 0;
+pub const WA: i32 = 1;
+pub const PE: i32 = 2;
+/// testlib reports "partially correct" as the exit code 16 plus the percent.
+pub const PARTIAL_BASE: i32 = 16;
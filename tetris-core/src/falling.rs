@@ -0,0 +1,263 @@
+use tracing::debug;
+
+use crate::{Action, DoesNotFit, RotationSystem, TetrisBoard, Tetromino};
+
+/// The way the tetromino gets from the top of the board to its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, derive_more::Display)]
+pub enum Gravity {
+    /// The tetromino moves above the stack and then drops straight down when the actions end.
+    #[default]
+    #[display(fmt = "drop")]
+    Drop,
+    /// The tetromino spawns inside the board, and it falls one line per `down` action, so it can
+    /// get under the overhangs (see [`FallingTetromino`]).
+    #[display(fmt = "step")]
+    Step,
+}
+
+crate::impl_from_str!(Gravity { Drop, Step });
+
+/// A tetromino inside the board with the step-by-step gravity: every action is checked against
+/// the occupied cells (including every wall kick of the rotation system), so it can be slid
+/// under the overhangs and rotated into the holes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FallingTetromino {
+    pub tetromino: Tetromino,
+    /// The line of the top of the tetromino (counted from the bottom, starting with 0).
+    pub top_line: usize,
+    rotation_system: RotationSystem,
+}
+
+impl FallingTetromino {
+    /// Puts the tetromino at the top of the board.
+    pub fn spawn(
+        board: &TetrisBoard,
+        tetromino: Tetromino,
+        rotation_system: RotationSystem,
+    ) -> Result<Self, DoesNotFit> {
+        let falling_tetromino = Self {
+            tetromino,
+            top_line: board.height() - 1,
+            rotation_system,
+        };
+        if board.fits(&tetromino, falling_tetromino.top_line) {
+            Ok(falling_tetromino)
+        } else {
+            debug!("Tetromino could not get spawned on the board");
+            Err(DoesNotFit)
+        }
+    }
+
+    /// Applies the action unless the tetromino would leave the board or overlap with the
+    /// occupied cells, and tells whether the tetromino has moved. When `down` fails, the
+    /// tetromino has landed.
+    pub fn apply_action(&mut self, action: Action, board: &TetrisBoard) -> bool {
+        let mut moved = *self;
+        match action {
+            Action::Down => match self.top_line.checked_sub(1) {
+                Some(top_line) => moved.top_line = top_line,
+                None => return false,
+            },
+            Action::Rotate => {
+                return match self
+                    .rotation_system
+                    .rotations(&self.tetromino, board.width())
+                    .into_iter()
+                    .filter_map(|(tetromino, line_shift)| {
+                        let top_line = self.top_line as isize + line_shift;
+                        if top_line >= 0 && board.fits(&tetromino, top_line as usize) {
+                            Some((tetromino, top_line as usize))
+                        } else {
+                            None
+                        }
+                    })
+                    .next()
+                {
+                    Some((tetromino, top_line)) => {
+                        self.tetromino = tetromino;
+                        self.top_line = top_line;
+                        true
+                    }
+                    None => false,
+                };
+            }
+            Action::Hold => return false,
+            _ => moved.tetromino.apply_action(action, board.width()),
+        }
+        if moved != *self && board.fits(&moved.tetromino, moved.top_line) {
+            *self = moved;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drops the tetromino as low as it can fall from where it is and locks it on the board, and
+    /// returns the line where the top of the tetromino has landed.
+    pub fn lock(mut self, board: &mut TetrisBoard) -> usize {
+        while self.apply_action(Action::Down, board) {}
+        debug!(
+            "Tetromino {} shifted by {} will get locked on the line {}",
+            self.tetromino.kind, self.tetromino.position, self.top_line
+        );
+        board
+            .place_tetromino(&self.tetromino, self.top_line)
+            .expect("the falling tetromino always fits into the board");
+        self.top_line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+
+    use super::*;
+    use crate::TetrominoKind;
+
+    #[test]
+    fn test_tuck() {
+        let mut board = TetrisBoard::new(8, 6);
+        let overhang = Tetromino {
+            kind: TetrominoKind::I,
+            position: 5,
+            rotation: 90,
+        };
+        board.place_tetromino(&overhang, 1).unwrap();
+
+        let mut falling_tetromino = FallingTetromino::spawn(
+            &board,
+            Tetromino {
+                kind: TetrominoKind::I,
+                position: 1,
+                rotation: 0,
+            },
+            RotationSystem::Classic,
+        )
+        .unwrap();
+        assert_eq!(falling_tetromino.top_line, 5);
+        assert!(falling_tetromino.apply_action(Action::Rotate, &board));
+        for _ in 0..5 {
+            assert!(falling_tetromino.apply_action(Action::Down, &board));
+        }
+        assert!(!falling_tetromino.apply_action(Action::Down, &board));
+        // There is no room to rotate at the bottom
+        assert!(!falling_tetromino.apply_action(Action::Rotate, &board));
+        for _ in 0..4 {
+            assert!(falling_tetromino.apply_action(Action::ShiftRight, &board));
+        }
+        assert!(!falling_tetromino.apply_action(Action::ShiftRight, &board));
+        assert_eq!(falling_tetromino.tetromino.position, 5);
+        falling_tetromino.lock(&mut board);
+        assert_snapshot!(board.to_string());
+    }
+
+    #[test]
+    fn test_blocked_moves() {
+        let mut board = TetrisBoard::new(4, 6);
+        board
+            .place_tetromino(
+                &Tetromino {
+                    kind: TetrominoKind::O,
+                    position: 1,
+                    rotation: 0,
+                },
+                3,
+            )
+            .unwrap();
+
+        let mut falling_tetromino = FallingTetromino::spawn(
+            &board,
+            Tetromino {
+                kind: TetrominoKind::O,
+                position: 3,
+                rotation: 0,
+            },
+            RotationSystem::Classic,
+        )
+        .unwrap();
+        assert!(falling_tetromino.apply_action(Action::Down, &board));
+        assert!(falling_tetromino.apply_action(Action::Down, &board));
+        // The shift is blocked by the tetromino on the left until the tetromino falls below it
+        assert!(!falling_tetromino.apply_action(Action::ShiftLeft, &board));
+        assert!(falling_tetromino.apply_action(Action::Down, &board));
+        assert!(!falling_tetromino.apply_action(Action::ShiftLeft, &board));
+        assert!(falling_tetromino.apply_action(Action::Down, &board));
+        assert!(falling_tetromino.apply_action(Action::ShiftLeft, &board));
+        assert!(!falling_tetromino.apply_action(Action::Hold, &board));
+        falling_tetromino.lock(&mut board);
+        assert_snapshot!(board.to_string());
+
+        assert_eq!(
+            FallingTetromino::spawn(
+                &board,
+                Tetromino {
+                    kind: TetrominoKind::I,
+                    position: 1,
+                    rotation: 0,
+                },
+                RotationSystem::Classic,
+            ),
+            Err(DoesNotFit)
+        );
+    }
+
+    #[test]
+    fn test_srs_kicks() {
+        let mut board = TetrisBoard::new(6, 6);
+        board
+            .place_tetromino(
+                &Tetromino {
+                    kind: TetrominoKind::I,
+                    position: 1,
+                    rotation: 90,
+                },
+                0,
+            )
+            .unwrap();
+
+        // The horizontal I on the floor is kicked up when it rotates
+        let mut falling_tetromino = FallingTetromino::spawn(
+            &board,
+            Tetromino {
+                kind: TetrominoKind::I,
+                position: 1,
+                rotation: 90,
+            },
+            RotationSystem::Srs,
+        )
+        .unwrap();
+        for _ in 0..4 {
+            assert!(falling_tetromino.apply_action(Action::Down, &board));
+        }
+        assert_eq!(falling_tetromino.top_line, 1);
+        assert!(falling_tetromino.apply_action(Action::Rotate, &board));
+        assert_eq!(falling_tetromino.tetromino.position, 4);
+        assert_eq!(falling_tetromino.top_line, 4);
+
+        // The T turns around its center into the slot next to the I
+        let mut falling_tetromino = FallingTetromino::spawn(
+            &board,
+            Tetromino {
+                kind: TetrominoKind::T,
+                position: 5,
+                rotation: 270,
+            },
+            RotationSystem::Srs,
+        )
+        .unwrap();
+        for _ in 0..3 {
+            assert!(falling_tetromino.apply_action(Action::Down, &board));
+        }
+        assert!(falling_tetromino.apply_action(Action::Rotate, &board));
+        falling_tetromino.lock(&mut board);
+        assert_snapshot!(board.to_string());
+    }
+
+    #[test]
+    fn test_parse_gravity() {
+        for &gravity in &[Gravity::Drop, Gravity::Step] {
+            assert_eq!(gravity.to_string().parse(), Ok(gravity));
+        }
+        assert_eq!("fast".parse::<Gravity>(), Err(()));
+    }
+}
@@ -0,0 +1,666 @@
+//! The game engine that drives a game step by step without any I/O:
+//!
+//! ```
+//! use tetris_core::{Action, Game, Level};
+//!
+//! let mut game = Game::new(Level::builtin(9).unwrap(), [0; 32], 10);
+//! while !game.is_over() {
+//!     game.spawn_next();
+//!     game.apply_actions(&[Action::ShiftLeft]).unwrap();
+//! }
+//! assert!(game.tetrominoes() > 0);
+//! ```
+//!
+//! The interactor is a thin adapter that announces the spawned tetrominoes to the solution and
+//! parses its lines of actions, while the bots, the tests, and the simulations can play games
+//! in-process.
+
+use std::collections::VecDeque;
+
+use tracing::debug;
+
+use crate::{
+    Action, FallingTetromino, Goal, Gravity, Level, Random, Scoring, TetrisBoard, Tetromino,
+    TetrominoGenerator, GARBAGE_STREAM,
+};
+
+/// How the game has ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOver {
+    /// The goal is reached.
+    Ok,
+    /// The tetromino has not fit into the board.
+    Dead,
+    /// The limit of the tetrominoes is reached.
+    Survived,
+}
+
+/// The result of the actions for the current tetromino.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    /// The tetromino has got locked on the board with its top on the given line (counted from the
+    /// bottom, starting with 0, before the full lines are cleared).
+    Locked {
+        tetromino: Tetromino,
+        top_line: usize,
+        cleared_lines: u64,
+        points: u64,
+    },
+    /// The tetromino is held, and the returned tetromino has replaced it (it gets the next
+    /// actions, and it cannot be held again).
+    Held(Tetromino),
+    /// The tetromino has not fit into the board where it has been placed (or where it has
+    /// spawned with the step-by-step gravity), so the game is over.
+    ToppedOut(Tetromino),
+}
+
+/// The actions that the rules of the level do not allow, with the index of the offending action.
+/// The game stays as it was before the actions, so the actions for the current tetromino can be
+/// retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum IllegalActions {
+    #[display(fmt = "`hold` is not allowed on the level")]
+    HoldNotAllowed(usize),
+    #[display(fmt = "the tetromino has already replaced the held one")]
+    HoldTwice(usize),
+    #[display(fmt = "`hold` must be the last action")]
+    ActionsAfterHold(usize),
+    #[display(fmt = "`down` is only allowed with the step-by-step gravity")]
+    DownNotAllowed(usize),
+    #[display(fmt = "the sequence has no tetromino to replace the held one")]
+    SequenceEnded(usize),
+}
+
+impl IllegalActions {
+    /// The index of the offending action.
+    pub fn index(self) -> usize {
+        match self {
+            Self::HoldNotAllowed(index)
+            | Self::HoldTwice(index)
+            | Self::ActionsAfterHold(index)
+            | Self::DownNotAllowed(index)
+            | Self::SequenceEnded(index) => index,
+        }
+    }
+}
+
+/// The tetromino that waits for its actions.
+#[derive(Debug, Clone, Copy)]
+struct CurrentTetromino {
+    tetromino: Tetromino,
+    /// Whether the tetromino has replaced the held one.
+    has_held: bool,
+}
+
+pub struct Game {
+    level: Level,
+    generator: TetrominoGenerator,
+    /// The tetrominoes that are already generated and revealed in the preview.
+    upcoming_tetrominoes: VecDeque<Tetromino>,
+    /// The tetromino put aside with the `hold` action (as it has spawned).
+    held_tetromino: Option<Tetromino>,
+    current_tetromino: Option<CurrentTetromino>,
+    board: TetrisBoard,
+    /// The random generator for the holes of the garbage rows.
+    garbage_random: Random,
+    /// The holes of the garbage rows that have risen after the last locked tetromino.
+    risen_garbage: Vec<usize>,
+    lines: u64,
+    scoring: Scoring,
+    /// The number of lines or the score to reach depending on the goal of the level.
+    score_limit: u64,
+    tetrominoes: u64,
+    is_topped_out: bool,
+}
+
+impl Game {
+    pub fn new(level: Level, seed: [u8; 32], score_limit: u64) -> Self {
+        Self {
+            generator: TetrominoGenerator::new(&level, seed),
+            upcoming_tetrominoes: VecDeque::with_capacity(level.preview + 1),
+            held_tetromino: None,
+            current_tetromino: None,
+            board: level.new_board(),
+            garbage_random: Random::from_seed_and_stream(seed, GARBAGE_STREAM),
+            risen_garbage: Vec::new(),
+            level,
+            lines: 0,
+            scoring: Scoring::default(),
+            score_limit,
+            tetrominoes: 0,
+            is_topped_out: false,
+        }
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn board(&self) -> &TetrisBoard {
+        &self.board
+    }
+
+    pub fn lines(&self) -> u64 {
+        self.lines
+    }
+
+    /// The columns of the holes (starting with 1) of the garbage rows that have risen after the
+    /// last locked tetromino, in the order they have risen (so the last one is at the bottom).
+    pub fn risen_garbage(&self) -> &[usize] {
+        &self.risen_garbage
+    }
+
+    pub fn scoring(&self) -> &Scoring {
+        &self.scoring
+    }
+
+    pub fn score_limit(&self) -> u64 {
+        self.score_limit
+    }
+
+    /// The number of the tetrominoes that the game has given out (including the held one).
+    pub fn tetrominoes(&self) -> u64 {
+        self.tetrominoes
+    }
+
+    /// The tetrominoes that have got locked on the board (the held one has not).
+    pub fn locked_tetrominoes(&self) -> u64 {
+        self.tetrominoes - u64::from(self.held_tetromino.is_some())
+    }
+
+    /// The tetromino that waits for its actions.
+    pub fn current_tetromino(&self) -> Option<&Tetromino> {
+        self.current_tetromino
+            .as_ref()
+            .map(|current_tetromino| &current_tetromino.tetromino)
+    }
+
+    /// The tetrominoes that come after the current one as far as the level reveals them.
+    pub fn preview(&self) -> impl Iterator<Item = &Tetromino> {
+        self.upcoming_tetrominoes.iter().take(self.level.preview)
+    }
+
+    pub fn held_tetromino(&self) -> Option<&Tetromino> {
+        self.held_tetromino.as_ref()
+    }
+
+    /// The lines or the score depending on the goal of the level.
+    pub fn progress(&self) -> u64 {
+        match self.level.goal {
+            Goal::Lines => self.lines,
+            Goal::Score => self.scoring.points,
+        }
+    }
+
+    /// The fewest tetrominoes that can clear the lines of the goal on the empty board (every
+    /// tetromino fills 4 cells), which is the best result of the sprint.
+    pub fn sprint_par(&self) -> u64 {
+        let cells = self.score_limit * self.level.board_width as u64;
+        cells / 4 + u64::from(cells % 4 != 0)
+    }
+
+    pub fn is_goal_reached(&self) -> bool {
+        self.progress() >= self.score_limit
+    }
+
+    /// Tells how the game has ended (if it has). The game only ends between the tetrominoes.
+    pub fn game_over(&self) -> Option<GameOver> {
+        if self.is_topped_out {
+            Some(GameOver::Dead)
+        } else if self.current_tetromino.is_some() {
+            None
+        } else if self.level.mode.ends_at_goal() && self.is_goal_reached() {
+            Some(GameOver::Ok)
+        } else if matches!(self.level.piece_limit, Some(piece_limit) if self.locked_tetrominoes() >= piece_limit)
+        {
+            Some(GameOver::Survived)
+        } else if self.upcoming_tetrominoes.is_empty() && self.generator.is_exhausted() {
+            // The sequence of the level has ended
+            Some(GameOver::Survived)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.game_over().is_some()
+    }
+
+    /// Takes the next tetromino and generates the ones for the preview in advance. The
+    /// tetrominoes are generated in the same order regardless of the preview, so a game has the
+    /// same sequence with any preview.
+    fn next_tetromino(&mut self) -> Tetromino {
+        while self.upcoming_tetrominoes.len() <= self.level.preview
+            && !self.generator.is_exhausted()
+        {
+            self.upcoming_tetrominoes
+                .push_back(self.generator.next_tetromino());
+        }
+        self.tetrominoes += 1;
+        self.upcoming_tetrominoes
+            .pop_front()
+            .expect("the upcoming tetrominoes are generated above")
+    }
+
+    /// Gives out the next tetromino, which becomes the current one.
+    ///
+    /// # Panics
+    ///
+    /// When the game is over or the current tetromino has not got its actions yet.
+    pub fn spawn_next(&mut self) -> Tetromino {
+        assert!(!self.is_over(), "the game is over");
+        assert!(
+            self.current_tetromino.is_none(),
+            "the current tetromino has not got its actions yet"
+        );
+        let tetromino = self.next_tetromino();
+        self.current_tetromino = Some(CurrentTetromino {
+            tetromino,
+            has_held: false,
+        });
+        tetromino
+    }
+
+    /// Applies the actions to the current tetromino and locks it, or holds it when the last
+    /// action is `hold`.
+    ///
+    /// # Panics
+    ///
+    /// When there is no current tetromino (see [`Game::spawn_next`]).
+    pub fn apply_actions(&mut self, actions: &[Action]) -> Result<StepOutcome, IllegalActions> {
+        let current_tetromino = self
+            .current_tetromino
+            .expect("there is no current tetromino");
+        let mut tetromino = current_tetromino.tetromino;
+        let mut falling_tetromino = match self.level.gravity {
+            Gravity::Drop => None,
+            Gravity::Step => {
+                match FallingTetromino::spawn(&self.board, tetromino, self.level.rotation_system) {
+                    Ok(falling_tetromino) => Some(falling_tetromino),
+                    Err(_) => return Ok(self.top_out(tetromino)),
+                }
+            }
+        };
+
+        // With the step-by-step gravity the tetromino lands when it cannot move down, and the
+        // rest of the actions are ignored
+        for (index, &action) in actions.iter().enumerate() {
+            match action {
+                Action::Hold => {
+                    if !self.level.allows_hold {
+                        return Err(IllegalActions::HoldNotAllowed(index));
+                    }
+                    if current_tetromino.has_held {
+                        return Err(IllegalActions::HoldTwice(index));
+                    }
+                    if index + 1 != actions.len() {
+                        return Err(IllegalActions::ActionsAfterHold(index));
+                    }
+                    if self.held_tetromino.is_none()
+                        && self.upcoming_tetrominoes.is_empty()
+                        && self.generator.is_exhausted()
+                    {
+                        return Err(IllegalActions::SequenceEnded(index));
+                    }
+                    return Ok(StepOutcome::Held(self.hold(current_tetromino.tetromino)));
+                }
+                _ => match falling_tetromino.as_mut() {
+                    Some(falling_tetromino) => {
+                        if !falling_tetromino.apply_action(action, &self.board)
+                            && action == Action::Down
+                        {
+                            break;
+                        }
+                    }
+                    None if action == Action::Down => {
+                        return Err(IllegalActions::DownNotAllowed(index))
+                    }
+                    None if action == Action::Rotate => {
+                        tetromino.rotate(self.level.rotation_system, self.board.width());
+                    }
+                    None => tetromino.apply_action(action, self.board.width()),
+                },
+            }
+        }
+
+        let (tetromino, top_line) = match falling_tetromino {
+            Some(falling_tetromino) => (
+                falling_tetromino.tetromino,
+                falling_tetromino.lock(&mut self.board),
+            ),
+            None => {
+                debug_assert!(tetromino.position >= 1);
+                debug_assert!(tetromino.position <= self.board.width() - tetromino.width() + 1);
+                match self.board.try_apply_tetromino(tetromino) {
+                    Ok(top_line) => (tetromino, top_line),
+                    Err(_) => return Ok(self.top_out(tetromino)),
+                }
+            }
+        };
+        self.current_tetromino = None;
+
+        let cleared_lines = self.board.clean_full_lines();
+        self.lines += cleared_lines;
+        let points = self.scoring.record(cleared_lines, self.board.is_empty());
+        self.raise_garbage();
+        Ok(StepOutcome::Locked {
+            tetromino,
+            top_line,
+            cleared_lines,
+            points,
+        })
+    }
+
+    /// Puts the tetromino aside and brings back the previously held one, or the next one when
+    /// nothing is held yet.
+    fn hold(&mut self, tetromino: Tetromino) -> Tetromino {
+        let replacing_tetromino = match self.held_tetromino.replace(tetromino) {
+            Some(held_tetromino) => held_tetromino,
+            None => self.next_tetromino(),
+        };
+        self.current_tetromino = Some(CurrentTetromino {
+            tetromino: replacing_tetromino,
+            has_held: true,
+        });
+        replacing_tetromino
+    }
+
+    /// Raises the garbage rows that are due after the tetromino has got locked. The game is over
+    /// when the garbage pushes the stack out of the board.
+    fn raise_garbage(&mut self) {
+        self.risen_garbage.clear();
+        let rows = match &self.level.garbage {
+            Some(garbage) => garbage.rows_after(self.locked_tetrominoes()),
+            None => return,
+        };
+        for _ in 0..rows {
+            let hole = self.garbage_random.gen_index(self.board.width());
+            self.risen_garbage.push(hole + 1);
+            if self.board.raise_garbage(hole).is_err() {
+                self.is_topped_out = true;
+                break;
+            }
+        }
+    }
+
+    fn top_out(&mut self, tetromino: Tetromino) -> StepOutcome {
+        debug!("The game is over since the tetromino has not fit into the board");
+        self.current_tetromino = None;
+        self.is_topped_out = true;
+        StepOutcome::ToppedOut(tetromino)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Garbage, Mode, Sequence, SequenceEnd, TetrominoKind};
+
+    #[test]
+    fn test_preview() {
+        let level = Level::builtin(9).unwrap();
+        let mut game = Game::new(level.clone(), [0; 32], 1000);
+        let mut game_with_preview = Game::new(
+            Level {
+                preview: 3,
+                ..level
+            },
+            [0; 32],
+            1000,
+        );
+        let tetrominoes = (0..100)
+            .map(|_| {
+                let tetromino = game.spawn_next();
+                game.current_tetromino = None;
+                tetromino
+            })
+            .collect::<Vec<_>>();
+        for (index, tetromino) in tetrominoes.iter().take(97).enumerate() {
+            assert!(game_with_preview.spawn_next() == *tetromino);
+            assert!(game_with_preview
+                .preview()
+                .eq(tetrominoes[index + 1..index + 4].iter()));
+            game_with_preview.current_tetromino = None;
+        }
+    }
+
+    #[test]
+    fn test_hold() {
+        let level = Level {
+            allows_hold: true,
+            ..Level::builtin(9).unwrap()
+        };
+        let mut game = Game::new(level.clone(), [0; 32], 10);
+        let mut generator = TetrominoGenerator::new(&level, [0; 32]);
+        let first = game.spawn_next();
+        assert!(first == generator.next_tetromino());
+        assert_eq!(game.held_tetromino(), None);
+
+        assert_eq!(
+            game.apply_actions(&[Action::Hold, Action::Rotate]),
+            Err(IllegalActions::ActionsAfterHold(0))
+        );
+        let second = match game.apply_actions(&[Action::Rotate, Action::Hold]) {
+            Ok(StepOutcome::Held(second)) => second,
+            outcome => panic!("{:?}", outcome),
+        };
+        assert!(second == generator.next_tetromino());
+        assert!(game.held_tetromino() == Some(&first));
+        assert_eq!(game.tetrominoes(), 2);
+        assert_eq!(game.locked_tetrominoes(), 1);
+        assert_eq!(
+            game.apply_actions(&[Action::Hold]),
+            Err(IllegalActions::HoldTwice(0))
+        );
+        assert!(matches!(
+            game.apply_actions(&[]),
+            Ok(StepOutcome::Locked { .. })
+        ));
+
+        // The held tetromino comes back as it has spawned
+        game.spawn_next();
+        assert!(game.apply_actions(&[Action::Hold]) == Ok(StepOutcome::Held(first)));
+
+        let mut game = Game::new(Level::builtin(9).unwrap(), [0; 32], 10);
+        game.spawn_next();
+        assert_eq!(
+            game.apply_actions(&[Action::Hold]),
+            Err(IllegalActions::HoldNotAllowed(0))
+        );
+        assert_eq!(
+            game.apply_actions(&[Action::Down]),
+            Err(IllegalActions::DownNotAllowed(0))
+        );
+    }
+
+    #[test]
+    fn test_step_gravity() {
+        let level = Level {
+            tetromino_kinds: &[TetrominoKind::O],
+            gravity: Gravity::Step,
+            board_width: 4,
+            board_height: 4,
+            ..Level::builtin(0).unwrap()
+        };
+        let mut game = Game::new(level, [0; 32], 10);
+        game.spawn_next();
+        // The tetromino lands on the third `down`, so the rest of the actions are ignored
+        assert!(matches!(
+            game.apply_actions(&[
+                Action::Down,
+                Action::Down,
+                Action::Down,
+                Action::ShiftRight,
+                Action::Hold
+            ]),
+            Ok(StepOutcome::Locked {
+                top_line: 1,
+                cleared_lines: 0,
+                ..
+            })
+        ));
+        assert!(!game.board().is_empty());
+
+        // The tetrominoes pile up until they do not fit into the board
+        let mut outcomes = Vec::new();
+        while !game.is_over() {
+            let tetromino = game.spawn_next();
+            let mut actions = vec![Action::ShiftLeft; tetromino.position];
+            if outcomes.len() % 2 == 0 {
+                actions = vec![Action::ShiftRight; 3];
+            }
+            outcomes.push(game.apply_actions(&actions).unwrap());
+        }
+        assert_eq!(game.game_over(), Some(GameOver::Dead));
+        assert!(matches!(outcomes.last(), Some(StepOutcome::ToppedOut(_))));
+    }
+
+    #[test]
+    fn test_game_over() {
+        for &(mode, lines, tetrominoes, held, expected) in &[
+            (Mode::Marathon, 9, 19, false, None),
+            (Mode::Marathon, 10, 5, false, Some(GameOver::Ok)),
+            (Mode::Marathon, 9, 20, true, None),
+            (Mode::Marathon, 9, 21, true, Some(GameOver::Survived)),
+            (Mode::Sprint, 10, 20, false, Some(GameOver::Ok)),
+            (Mode::Sprint, 0, 20, false, Some(GameOver::Survived)),
+            // The ultra game goes on after the goal until it runs out of the tetrominoes
+            (Mode::Ultra, 15, 19, false, None),
+            (Mode::Ultra, 15, 20, false, Some(GameOver::Survived)),
+        ] {
+            let level = Level {
+                mode,
+                piece_limit: Some(20),
+                allows_hold: true,
+                ..Level::builtin(9).unwrap()
+            };
+            let mut game = Game::new(level, [0; 32], 10);
+            game.lines = lines;
+            game.tetrominoes = tetrominoes;
+            if held {
+                game.held_tetromino = Some(game.next_tetromino());
+                game.tetrominoes = tetrominoes;
+            }
+            assert_eq!(
+                game.game_over(),
+                expected,
+                "{} {} {}",
+                mode,
+                lines,
+                tetrominoes
+            );
+        }
+
+        // 10 lines of 10 cells take at least 25 tetrominoes, and 3 lines of 6 cells take 5
+        assert_eq!(Game::new(Level::default(), [0; 32], 10).sprint_par(), 25);
+        let narrow_level = Level {
+            board_width: 6,
+            ..Level::default()
+        };
+        assert_eq!(Game::new(narrow_level, [0; 32], 3).sprint_par(), 5);
+    }
+
+    #[test]
+    fn test_garbage() {
+        let level = Level {
+            garbage: Some(Garbage::Every {
+                interval: 2,
+                rows: 3,
+            }),
+            ..Level::builtin(9).unwrap()
+        };
+        let play = |level: Level| {
+            let mut game = Game::new(level, [3; 32], 10);
+            let mut tetrominoes = Vec::new();
+            let mut risen_garbage = Vec::new();
+            while !game.is_over() {
+                tetrominoes.push(game.spawn_next());
+                game.apply_actions(&[]).unwrap();
+                risen_garbage.push(game.risen_garbage().to_vec());
+            }
+            (game, tetrominoes, risen_garbage)
+        };
+        let (game, tetrominoes, risen_garbage) = play(level.clone());
+        assert_eq!(game.game_over(), Some(GameOver::Dead));
+        assert_eq!(risen_garbage[0], Vec::<usize>::new());
+        assert_eq!(risen_garbage[1].len(), 3);
+        assert!(risen_garbage[1].iter().all(|hole| (1..=10).contains(hole)));
+        // Every garbage row has a single hole
+        let bottom_line = &game.board().lines()[0];
+        assert_eq!(
+            bottom_line.iter().filter(|cell| cell.is_occupied()).count(),
+            9
+        );
+
+        // The garbage does not change the tetrominoes, and the game is reproducible
+        let (_, same_tetrominoes, same_garbage) = play(level.clone());
+        assert!(same_tetrominoes == tetrominoes);
+        assert_eq!(same_garbage, risen_garbage);
+        let (_, tetrominoes_without_garbage, _) = play(Level {
+            garbage: None,
+            ..level
+        });
+        assert!(tetrominoes_without_garbage[..tetrominoes.len()] == tetrominoes[..]);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let level = Level {
+            preview: 2,
+            allows_hold: true,
+            sequence: Some(Sequence::parse(vec!["O 1", "O 3", "O 5"], SequenceEnd::Stop).unwrap()),
+            ..Level::builtin(9).unwrap()
+        };
+        let mut game = Game::new(level, [0; 32], 10);
+        let first = game.spawn_next();
+        assert_eq!(first.position, 1);
+        assert_eq!(
+            game.preview()
+                .map(|tetromino| tetromino.position)
+                .collect::<Vec<_>>(),
+            vec![3, 5]
+        );
+        game.apply_actions(&[]).unwrap();
+        game.spawn_next();
+        // The preview does not go past the end of the sequence
+        assert_eq!(game.preview().count(), 1);
+        game.apply_actions(&[]).unwrap();
+        let last = game.spawn_next();
+        assert_eq!(last.position, 5);
+        assert_eq!(
+            game.apply_actions(&[Action::Hold]),
+            Err(IllegalActions::SequenceEnded(0))
+        );
+        game.apply_actions(&[]).unwrap();
+        assert_eq!(game.game_over(), Some(GameOver::Survived));
+        assert_eq!(game.locked_tetrominoes(), 3);
+    }
+
+    #[test]
+    fn test_play() {
+        // The same seed gives the same game
+        let play = || {
+            let mut game = Game::new(Level::builtin(9).unwrap(), [7; 32], 10);
+            let mut points = 0;
+            while !game.is_over() {
+                let tetromino = game.spawn_next();
+                let actions = vec![Action::ShiftLeft; tetromino.position % 4];
+                if let StepOutcome::Locked {
+                    points: step_points,
+                    ..
+                } = game.apply_actions(&actions).unwrap()
+                {
+                    points += step_points;
+                }
+            }
+            assert_eq!(points, game.scoring().points);
+            (
+                game.game_over(),
+                game.tetrominoes(),
+                game.board().to_string(),
+            )
+        };
+        assert_eq!(play(), play());
+        assert_eq!(play().0, Some(GameOver::Dead));
+    }
+}
@@ -0,0 +1,130 @@
+//! The garbage rows that rise from the bottom of the board: every row is full except for a single
+//! hole in a random column, and it pushes the stack up by a line.
+
+/// The stream of the random generator for the holes of the garbage rows, so the garbage does not
+/// change the sequence of the tetrominoes of the same seed.
+pub const GARBAGE_STREAM: u64 = 1;
+
+/// When the garbage rows rise (after the tetromino gets locked and the full lines are cleared).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Garbage {
+    /// The given number of rows after every given number of the locked tetrominoes.
+    Every { interval: u64, rows: usize },
+    /// The rows after the given numbers of the locked tetrominoes, as `(tetrominoes, rows)`
+    /// pairs.
+    Schedule(Vec<(u64, usize)>),
+}
+
+/// `10` (a row after every 10 tetrominoes), `10 2` (2 rows after every 10 tetrominoes), or
+/// `at 5:1 20:3` (a row after the 5th tetromino and 3 rows after the 20th one).
+impl std::str::FromStr for Garbage {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = s.split_ascii_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            ["at", schedule @ ..] if !schedule.is_empty() => Ok(Self::Schedule(
+                schedule
+                    .iter()
+                    .map(|rise| {
+                        let (tetrominoes, rows) = rise.split_once(':').ok_or(())?;
+                        match (tetrominoes.parse(), rows.parse()) {
+                            (Ok(tetrominoes), Ok(rows)) if tetrominoes > 0 && rows > 0 => {
+                                Ok((tetrominoes, rows))
+                            }
+                            _ => Err(()),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            [interval] => Self::every(interval, "1"),
+            [interval, rows] => Self::every(interval, rows),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Garbage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Every { interval, rows } => write!(f, "{} {}", interval, rows),
+            Self::Schedule(schedule) => {
+                write!(f, "at")?;
+                for (tetrominoes, rows) in schedule {
+                    write!(f, " {}:{}", tetrominoes, rows)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Garbage {
+    fn every(interval: &str, rows: &str) -> Result<Self, ()> {
+        match (interval.parse(), rows.parse()) {
+            (Ok(interval), Ok(rows)) if interval > 0 && rows > 0 => {
+                Ok(Self::Every { interval, rows })
+            }
+            _ => Err(()),
+        }
+    }
+
+    /// The number of the rows that rise after the given number of the tetrominoes is locked.
+    pub fn rows_after(&self, locked_tetrominoes: u64) -> usize {
+        match self {
+            Self::Every { interval, rows } if locked_tetrominoes % interval == 0 => *rows,
+            Self::Every { .. } => 0,
+            Self::Schedule(schedule) => schedule
+                .iter()
+                .filter(|&&(tetrominoes, _)| tetrominoes == locked_tetrominoes)
+                .map(|&(_, rows)| rows)
+                .sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_garbage() {
+        assert_eq!(
+            "10".parse(),
+            Ok(Garbage::Every {
+                interval: 10,
+                rows: 1
+            })
+        );
+        assert_eq!(
+            "8 2".parse(),
+            Ok(Garbage::Every {
+                interval: 8,
+                rows: 2
+            })
+        );
+        let schedule: Garbage = "at 5:1 20:3".parse().unwrap();
+        assert_eq!(schedule, Garbage::Schedule(vec![(5, 1), (20, 3)]));
+        assert_eq!(schedule.to_string(), "at 5:1 20:3");
+        for invalid in &["", "0", "10 0", "at", "at 5", "at 0:1", "every 10", "1 2 3"] {
+            assert_eq!(invalid.parse::<Garbage>(), Err(()), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_rows_after() {
+        let every = Garbage::Every {
+            interval: 4,
+            rows: 2,
+        };
+        assert_eq!(
+            (1..=8).map(|n| every.rows_after(n)).collect::<Vec<_>>(),
+            vec![0, 0, 0, 2, 0, 0, 0, 2]
+        );
+        let schedule = Garbage::Schedule(vec![(2, 1), (3, 2), (3, 1)]);
+        assert_eq!(
+            (1..=4).map(|n| schedule.rows_after(n)).collect::<Vec<_>>(),
+            vec![0, 1, 3, 0]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+
+use crate::{Level, Random, Sequence, SequenceEnd, Tetromino, TetrominoKind};
+
+/// The way the next tetromino kind is picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, derive_more::Display)]
+pub enum Randomizer {
+    /// Every kind is equally likely regardless of the previous tetrominoes.
+    #[default]
+    #[display(fmt = "uniform")]
+    Uniform,
+    /// All the kinds are shuffled into a bag, and the tetrominoes are taken from the bag until
+    /// it is empty (the 7-bag of the modern games when all the seven kinds are on the level).
+    #[display(fmt = "bag")]
+    Bag,
+    /// A kind is rerolled (up to [`HISTORY_ROLLS`] times) while it is one of the last
+    /// [`HISTORY_LENGTH`] tetrominoes, as in the TGM games.
+    #[display(fmt = "history")]
+    History,
+}
+
+pub const HISTORY_LENGTH: usize = 4;
+pub const HISTORY_ROLLS: usize = 4;
+
+crate::impl_from_str!(Randomizer { Uniform, Bag | "7-bag", History | "tgm" });
+
+/// Picks the next tetrominoes and their initial positions from the seed of the game (or takes them
+/// from the sequence of the level).
+pub struct TetrominoGenerator {
+    random: Random,
+    tetromino_kinds: &'static [TetrominoKind],
+    randomizer: Randomizer,
+    board_width: usize,
+    bag: Vec<TetrominoKind>,
+    history: VecDeque<TetrominoKind>,
+    sequence: Option<Sequence>,
+    /// The number of the tetrominoes taken from the sequence.
+    sequence_index: usize,
+}
+
+impl TetrominoGenerator {
+    /// The version of the generation algorithm. The sequences of the tests must never change
+    /// within a version, so any change of [`Random`] or [`TetrominoGenerator`] that changes them
+    /// needs a new version (and the golden tests of the interactor will fail without it).
+    pub const VERSION: u32 = 1;
+
+    pub fn new(level: &Level, seed: [u8; 32]) -> Self {
+        Self {
+            random: Random::from_seed(seed),
+            tetromino_kinds: level.tetromino_kinds,
+            randomizer: level.randomizer,
+            board_width: level.board_width,
+            bag: Vec::with_capacity(level.tetromino_kinds.len()),
+            history: VecDeque::with_capacity(HISTORY_LENGTH + 1),
+            sequence: level.sequence.clone(),
+            sequence_index: 0,
+        }
+    }
+
+    fn roll_kind(&mut self) -> TetrominoKind {
+        self.tetromino_kinds[self.random.gen_index(self.tetromino_kinds.len())]
+    }
+
+    fn next_kind(&mut self) -> TetrominoKind {
+        if self.tetromino_kinds.is_empty() {
+            return TetrominoKind::O;
+        }
+        match self.randomizer {
+            Randomizer::Uniform => self.roll_kind(),
+            Randomizer::Bag => {
+                if self.bag.is_empty() {
+                    self.bag.extend_from_slice(self.tetromino_kinds);
+                    // Fisher-Yates shuffle
+                    for index in (1..self.bag.len()).rev() {
+                        let other_index = self.random.gen_index(index + 1);
+                        self.bag.swap(index, other_index);
+                    }
+                }
+                self.bag.pop().expect("the bag is refilled above")
+            }
+            Randomizer::History => {
+                let mut kind = self.roll_kind();
+                for _ in 1..HISTORY_ROLLS {
+                    if !self.history.contains(&kind) {
+                        break;
+                    }
+                    kind = self.roll_kind();
+                }
+                self.history.push_back(kind);
+                if self.history.len() > HISTORY_LENGTH {
+                    self.history.pop_front();
+                }
+                kind
+            }
+        }
+    }
+
+    /// Whether the sequence of the level has ended and the game must end too (see
+    /// [`SequenceEnd::Stop`]).
+    pub fn is_exhausted(&self) -> bool {
+        matches!(&self.sequence, Some(sequence) if sequence.end == SequenceEnd::Stop && self.sequence_index >= sequence.len())
+    }
+
+    /// The next tetromino of the sequence, or a random one when there is no sequence (or it has
+    /// ended).
+    pub fn next_tetromino(&mut self) -> Tetromino {
+        if let Some(sequence) = &self.sequence {
+            if let Some(tetromino) = sequence.get(self.sequence_index) {
+                self.sequence_index += 1;
+                return tetromino;
+            }
+        }
+        let kind = self.next_kind();
+        let mut tetromino = Tetromino {
+            kind,
+            position: 0,
+            rotation: 0,
+        };
+        tetromino.position =
+            self.random.next_u32() as usize % (self.board_width + 1 - tetromino.width()) + 1;
+        tetromino
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_kinds(randomizer: Randomizer, count: usize) -> Vec<TetrominoKind> {
+        let level = Level {
+            randomizer,
+            ..Level::builtin(9).unwrap()
+        };
+        let mut generator = TetrominoGenerator::new(&level, *b"KhCup XVII 2020 Tetris randomize");
+        (0..count)
+            .map(|_| generator.next_tetromino().kind)
+            .collect()
+    }
+
+    fn count_repeats(kinds: &[TetrominoKind]) -> usize {
+        kinds.windows(2).filter(|pair| pair[0] == pair[1]).count()
+    }
+
+    #[test]
+    fn test_bag_randomizer() {
+        let kinds = generate_kinds(Randomizer::Bag, 7000);
+        for bag in kinds.chunks(7) {
+            for kind in &TetrominoKind::ALL {
+                assert!(bag.contains(kind), "{:?} is missing in {:?}", kind, bag);
+            }
+        }
+    }
+
+    #[test]
+    fn test_history_randomizer() {
+        let uniform_repeats = count_repeats(&generate_kinds(Randomizer::Uniform, 7000));
+        let history_repeats = count_repeats(&generate_kinds(Randomizer::History, 7000));
+        assert!(uniform_repeats > 700, "{}", uniform_repeats);
+        assert!(history_repeats < 350, "{}", history_repeats);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let level = Level::builtin(9).unwrap();
+        let random_tetrominoes = {
+            let mut generator = TetrominoGenerator::new(&level, [0; 32]);
+            (0..2)
+                .map(|_| generator.next_tetromino())
+                .collect::<Vec<_>>()
+        };
+        for &end in &[SequenceEnd::Stop, SequenceEnd::Repeat, SequenceEnd::Random] {
+            let level = Level {
+                sequence: Some(Sequence::parse(vec!["T 3", "I 10"], end).unwrap()),
+                ..level.clone()
+            };
+            let mut generator = TetrominoGenerator::new(&level, [0; 32]);
+            let tetrominoes = (0..4)
+                .map(|_| {
+                    let tetromino = generator.next_tetromino();
+                    format!("{} {}", tetromino.kind, tetromino.position)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(tetrominoes[..2], ["T 3", "I 10"]);
+            match end {
+                SequenceEnd::Stop => assert!(generator.is_exhausted()),
+                SequenceEnd::Repeat => assert_eq!(tetrominoes[2..], ["T 3", "I 10"]),
+                SequenceEnd::Random => assert_eq!(
+                    tetrominoes[2..],
+                    random_tetrominoes
+                        .iter()
+                        .map(|tetromino| format!("{} {}", tetromino.kind, tetromino.position))
+                        .collect::<Vec<_>>()[..]
+                ),
+            }
+            assert_eq!(generator.is_exhausted(), end == SequenceEnd::Stop);
+        }
+    }
+
+    #[test]
+    fn test_parse_randomizer() {
+        for &randomizer in &[Randomizer::Uniform, Randomizer::Bag, Randomizer::History] {
+            assert_eq!(randomizer.to_string().parse(), Ok(randomizer));
+        }
+        assert_eq!("7-bag".parse(), Ok(Randomizer::Bag));
+        assert_eq!("random".parse::<Randomizer>(), Err(()));
+    }
+}
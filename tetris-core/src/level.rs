@@ -0,0 +1,173 @@
+use crate::{
+    Garbage, Goal, Gravity, Mode, Randomizer, RotationSystem, Sequence, TetrisBoard,
+    TetrisBoardLine, TetrominoKind,
+};
+
+/// Game rules that differ between the levels of the problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Level {
+    pub number: u8,
+    /// Tetrominoes that the game picks the next tetromino from.
+    pub tetromino_kinds: &'static [TetrominoKind],
+    /// Whether the game tells the kind of the next tetromino or only its position (it only makes
+    /// sense to hide it when there is a single kind of tetrominoes on the level).
+    pub reveals_tetromino_kind: bool,
+    pub board_width: usize,
+    pub board_height: usize,
+    pub randomizer: Randomizer,
+    /// The number of the upcoming tetrominoes that the game reveals along with the current one
+    /// (up to [`Level::MAX_PREVIEW`]).
+    pub preview: usize,
+    /// Whether the solutions may put the current tetromino aside with the `hold` command.
+    pub allows_hold: bool,
+    pub gravity: Gravity,
+    pub rotation_system: RotationSystem,
+    /// Whether the number from the answer file is the number of lines to clear or the score.
+    pub goal: Goal,
+    pub mode: Mode,
+    /// The number of the tetrominoes to lock before the game ends (if the game has not ended
+    /// earlier).
+    pub piece_limit: Option<u64>,
+    /// When the garbage rows rise from the bottom of the board (never when not set).
+    pub garbage: Option<Garbage>,
+    /// The lines that the board starts with at the bottom (from the bottom to the top), which
+    /// must make a valid board (see [`TetrisBoard::with_lines`]).
+    pub starting_lines: Vec<TetrisBoardLine>,
+    /// The tetrominoes that the game gives out instead of the random ones (see
+    /// [`Sequence::validate`] for the ones that fit the level).
+    pub sequence: Option<Sequence>,
+}
+
+impl Level {
+    pub const BUILTIN_NUMBERS: &'static [u8] = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    /// The longest preview, as in the modern games (the game keeps it in a buffer).
+    pub const MAX_PREVIEW: usize = 6;
+
+    /// The tetrominoes of the intermediate levels 1-8 between the `O` of the level 0 and all the
+    /// kinds of the level 9: every pair of the levels adds new kinds up to all of them on the
+    /// levels 7-8, and the first level of the pair is played for the lines while the second one is
+    /// played for the score.
+    const INTERMEDIATE_TETROMINO_KINDS: [&'static [TetrominoKind]; 4] = [
+        &[TetrominoKind::O, TetrominoKind::I],
+        &[TetrominoKind::O, TetrominoKind::I, TetrominoKind::T],
+        &[
+            TetrominoKind::O,
+            TetrominoKind::I,
+            TetrominoKind::T,
+            TetrominoKind::L,
+            TetrominoKind::J,
+        ],
+        &TetrominoKind::ALL,
+    ];
+
+    pub fn builtin(number: u8) -> Option<Self> {
+        Some(match number {
+            0 => Self {
+                number,
+                tetromino_kinds: &[TetrominoKind::O],
+                reveals_tetromino_kind: false,
+                board_width: TetrisBoard::DEFAULT_WIDTH,
+                board_height: TetrisBoard::DEFAULT_HEIGHT,
+                randomizer: Randomizer::Uniform,
+                preview: 0,
+                allows_hold: false,
+                gravity: Gravity::Drop,
+                rotation_system: RotationSystem::Classic,
+                goal: Goal::Lines,
+                mode: Mode::Marathon,
+                piece_limit: None,
+                garbage: None,
+                starting_lines: Vec::new(),
+                sequence: None,
+            },
+            9 => Self {
+                number,
+                tetromino_kinds: &TetrominoKind::ALL,
+                reveals_tetromino_kind: true,
+                board_width: TetrisBoard::DEFAULT_WIDTH,
+                board_height: TetrisBoard::DEFAULT_HEIGHT,
+                randomizer: Randomizer::Uniform,
+                preview: 0,
+                allows_hold: false,
+                gravity: Gravity::Drop,
+                rotation_system: RotationSystem::Classic,
+                goal: Goal::Lines,
+                mode: Mode::Marathon,
+                piece_limit: None,
+                garbage: None,
+                starting_lines: Vec::new(),
+                sequence: None,
+            },
+            1..=8 => Self {
+                number,
+                tetromino_kinds: Self::INTERMEDIATE_TETROMINO_KINDS[usize::from(number - 1) / 2],
+                goal: if number % 2 == 1 {
+                    Goal::Lines
+                } else {
+                    Goal::Score
+                },
+                ..Self::builtin(9)?
+            },
+            _ => return None,
+        })
+    }
+
+    /// The board that the games of the level start with.
+    ///
+    /// # Panics
+    ///
+    /// When the starting lines do not make a valid board of the size of the level.
+    pub fn new_board(&self) -> TetrisBoard {
+        TetrisBoard::with_lines(self.board_width, self.board_height, &self.starting_lines)
+            .unwrap_or_else(|error| panic!("The starting board is invalid: {}", error))
+    }
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self::builtin(0).expect("level 0 is always defined")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_levels() {
+        for &number in Level::BUILTIN_NUMBERS {
+            let level = Level::builtin(number).unwrap();
+            assert_eq!(level.number, number);
+            assert!(!level.tetromino_kinds.is_empty());
+            assert!(level.reveals_tetromino_kind || level.tetromino_kinds.len() == 1);
+        }
+        assert_eq!(Level::builtin(10), None);
+
+        // Every level has the tetrominoes of the previous ones, and every pair of the intermediate
+        // levels adds new ones until the levels 7-8 have all of them
+        for pair in Level::BUILTIN_NUMBERS.windows(2) {
+            let previous = Level::builtin(pair[0]).unwrap();
+            let next = Level::builtin(pair[1]).unwrap();
+            assert!(previous
+                .tetromino_kinds
+                .iter()
+                .all(|kind| next.tetromino_kinds.contains(kind)));
+            if next.number % 2 == 1 && next.number < 9 {
+                assert!(
+                    next.tetromino_kinds.len() > previous.tetromino_kinds.len(),
+                    "level {}",
+                    next.number
+                );
+            } else {
+                assert_eq!(next.tetromino_kinds, previous.tetromino_kinds);
+            }
+        }
+        assert_eq!(Level::builtin(1).unwrap().goal, Goal::Lines);
+        assert_eq!(Level::builtin(2).unwrap().goal, Goal::Score);
+        assert_eq!(
+            Level::builtin(7).unwrap().tetromino_kinds,
+            &TetrominoKind::ALL
+        );
+        assert_eq!(Level::default().tetromino_kinds, &[TetrominoKind::O]);
+    }
+}
@@ -0,0 +1,35 @@
+//! Rules of the KhCup Tetris shared between the interactor and the solutions.
+
+mod action;
+mod board;
+pub mod exit_code;
+mod falling;
+mod game;
+mod garbage;
+mod generator;
+mod level;
+mod macros;
+mod player;
+mod random;
+mod rotation;
+mod scoring;
+mod sequence;
+mod tetromino;
+mod verdict;
+
+pub use action::Action;
+pub use board::{BoardError, DoesNotFit, TetrisBoard, TetrisBoardLine, TetrisCell};
+pub use falling::{FallingTetromino, Gravity};
+pub use game::{Game, GameOver, IllegalActions, StepOutcome};
+pub use garbage::{Garbage, GARBAGE_STREAM};
+pub use generator::{Randomizer, TetrominoGenerator};
+pub use level::Level;
+pub use player::{
+    builtin_player, ColumnsPlayer, FrolPlayer, Player, RandomPlayer, BUILTIN_PLAYERS,
+};
+pub use random::Random;
+pub use rotation::RotationSystem;
+pub use scoring::{Goal, Mode, Scoring};
+pub use sequence::{Sequence, SequenceEnd, SequenceError};
+pub use tetromino::{Tetromino, TetrominoKind};
+pub use verdict::{parse_seconds, Verdict};
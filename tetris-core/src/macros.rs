@@ -0,0 +1,22 @@
+//! The macros shared by the settings of the levels.
+
+/// Implements `FromStr` (with `()` as the error) for an enum of unit variants that derives
+/// `Display`: every variant is parsed from its display name, and from its aliases if any, e.g.
+/// `impl_from_str!(Randomizer { Uniform, Bag | "7-bag", History | "tgm" });`.
+#[macro_export]
+macro_rules! impl_from_str {
+    ($type:ident { $($variant:ident $(| $alias:literal)*),+ $(,)? }) => {
+        impl std::str::FromStr for $type {
+            type Err = ();
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $(
+                    if s == Self::$variant.to_string() $(|| s == $alias)* {
+                        return Ok(Self::$variant);
+                    }
+                )+
+                Err(())
+            }
+        }
+    };
+}
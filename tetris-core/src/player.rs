@@ -0,0 +1,238 @@
+//! The players that pick the actions for the tetrominoes in-process (see [`Game`]), including the
+//! reference bots that give the baselines of the levels.
+
+use crate::{
+    Action, Game, GameOver, IllegalActions, Random, RotationSystem, StepOutcome, TetrisBoard,
+    TetrisCell, Tetromino,
+};
+
+pub trait Player {
+    /// Picks the actions for the current tetromino of the game.
+    fn actions(&mut self, game: &Game, tetromino: &Tetromino) -> Vec<Action>;
+}
+
+impl Game {
+    /// Lets the player play the game till the end.
+    pub fn play(&mut self, player: &mut dyn Player) -> Result<GameOver, IllegalActions> {
+        while !self.is_over() {
+            let mut tetromino = self.spawn_next();
+            while let StepOutcome::Held(replacing_tetromino) =
+                self.apply_actions(&player.actions(self, &tetromino))?
+            {
+                tetromino = replacing_tetromino;
+            }
+        }
+        Ok(self
+            .game_over()
+            .expect("the loop only ends when the game is over"))
+    }
+}
+
+pub const BUILTIN_PLAYERS: &[&str] = &["random", "columns", "frol"];
+
+/// The reference bots by their names (the random one is seeded with the seed).
+pub fn builtin_player(name: &str, seed: [u8; 32]) -> Option<Box<dyn Player>> {
+    Some(match name {
+        "random" => Box::new(RandomPlayer::new(seed)),
+        "columns" => Box::new(ColumnsPlayer::default()),
+        "frol" => Box::new(FrolPlayer),
+        _ => return None,
+    })
+}
+
+/// The actions that move the tetromino to the left wall, rotate it there, and move it to the
+/// position, along with the tetromino where the actions have put it.
+fn actions_to(
+    board_width: usize,
+    rotation_system: RotationSystem,
+    tetromino: &Tetromino,
+    rotations: usize,
+    position: usize,
+) -> (Vec<Action>, Tetromino) {
+    let mut actions = vec![Action::ShiftLeft; tetromino.position - 1];
+    let mut moved_tetromino = Tetromino {
+        position: 1,
+        ..*tetromino
+    };
+    for _ in 0..rotations {
+        actions.push(Action::Rotate);
+        moved_tetromino.rotate(rotation_system, board_width);
+    }
+    while moved_tetromino.position != position {
+        let action = if moved_tetromino.position < position {
+            Action::ShiftRight
+        } else {
+            Action::ShiftLeft
+        };
+        let previous_position = moved_tetromino.position;
+        moved_tetromino.apply_action(action, board_width);
+        if moved_tetromino.position == previous_position {
+            break;
+        }
+        actions.push(action);
+    }
+    (actions, moved_tetromino)
+}
+
+/// Drops the tetrominoes at random positions with random rotations.
+pub struct RandomPlayer {
+    random: Random,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self {
+            random: Random::from_seed(seed),
+        }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn actions(&mut self, game: &Game, tetromino: &Tetromino) -> Vec<Action> {
+        let rotations = self.random.gen_index(4);
+        let position = self.random.gen_index(game.board().width()) + 1;
+        actions_to(
+            game.board().width(),
+            game.level().rotation_system,
+            tetromino,
+            rotations,
+            position,
+        )
+        .0
+    }
+}
+
+/// Drops the tetrominoes into every other column in turn without rotating them (the strategy of
+/// `solutions-level-0/frol.py`, which fills the board with the O tetrominoes without holes).
+#[derive(Debug, Default)]
+pub struct ColumnsPlayer {
+    shift: usize,
+}
+
+impl Player for ColumnsPlayer {
+    fn actions(&mut self, game: &Game, tetromino: &Tetromino) -> Vec<Action> {
+        let mut actions = vec![Action::ShiftLeft; tetromino.position - 1];
+        actions.extend(std::iter::repeat(Action::ShiftRight).take(self.shift));
+        self.shift = (self.shift + 2) % game.board().width();
+        actions
+    }
+}
+
+/// Tries every rotation and position, and picks the one with the lowest peak, and the fewest
+/// holes among those (the heuristic of `solutions-level-9/frol`, which plays with
+/// [`FrolPlayer::best_move`]).
+#[derive(Debug, Default)]
+pub struct FrolPlayer;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+struct Stats {
+    peak: usize,
+    holes: usize,
+}
+
+impl FrolPlayer {
+    fn stats(board: &TetrisBoard) -> Stats {
+        let peak = board
+            .lines()
+            .iter()
+            .rposition(|line| line.iter().any(TetrisCell::is_occupied))
+            .unwrap_or(0);
+        let holes = (0..board.width())
+            .map(|column| {
+                board
+                    .lines()
+                    .iter()
+                    .rev()
+                    .map(|line| line[column])
+                    .skip_while(|cell| !cell.is_occupied())
+                    .filter(|cell| !cell.is_occupied())
+                    .count()
+            })
+            .sum();
+        Stats { peak, holes }
+    }
+
+    /// The actions that drop the tetromino where the heuristic likes it the most, along with the
+    /// board after the tetromino has got locked there and the full lines have got cleared (none
+    /// when the tetromino fits nowhere).
+    pub fn best_move(
+        board: &TetrisBoard,
+        tetromino: &Tetromino,
+        rotation_system: RotationSystem,
+    ) -> Option<(Vec<Action>, TetrisBoard)> {
+        let mut best: Option<(Stats, Vec<Action>, TetrisBoard)> = None;
+        for position in 1..=board.width() {
+            for rotations in 0..4 {
+                let (actions, moved_tetromino) = actions_to(
+                    board.width(),
+                    rotation_system,
+                    tetromino,
+                    rotations,
+                    position,
+                );
+                if moved_tetromino.position != position {
+                    continue;
+                }
+                let mut board = board.clone();
+                if board.try_apply_tetromino(moved_tetromino).is_err() {
+                    continue;
+                }
+                board.clean_full_lines();
+                let stats = Self::stats(&board);
+                if best
+                    .as_ref()
+                    .map_or(true, |(best_stats, _, _)| stats < *best_stats)
+                {
+                    best = Some((stats, actions, board));
+                }
+            }
+        }
+        best.map(|(_, actions, board)| (actions, board))
+    }
+}
+
+impl Player for FrolPlayer {
+    fn actions(&mut self, game: &Game, tetromino: &Tetromino) -> Vec<Action> {
+        Self::best_move(game.board(), tetromino, game.level().rotation_system)
+            .map(|(actions, _)| actions)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    fn play_builtin(name: &str, level: u8, score_limit: u64) -> Game {
+        let mut game = Game::new(Level::builtin(level).unwrap(), [1; 32], score_limit);
+        let mut player = builtin_player(name, [2; 32]).unwrap();
+        game.play(player.as_mut()).unwrap();
+        game
+    }
+
+    #[test]
+    fn test_builtin_players() {
+        for &name in BUILTIN_PLAYERS {
+            for &level in Level::BUILTIN_NUMBERS {
+                let game = play_builtin(name, level, 100);
+                assert!(game.is_over(), "{} {}", name, level);
+            }
+        }
+        assert!(builtin_player("human", [0; 32]).is_none());
+    }
+
+    #[test]
+    fn test_baselines() {
+        // The columns player never leaves holes on the level 0
+        assert_eq!(
+            play_builtin("columns", 0, 1000).game_over(),
+            Some(GameOver::Ok)
+        );
+        // The frol player clears more lines than the random one
+        let frol = play_builtin("frol", 9, 1000);
+        let random = play_builtin("random", 9, 1000);
+        assert_eq!(frol.game_over(), Some(GameOver::Dead));
+        assert!(frol.lines() > random.lines() + 10);
+    }
+}
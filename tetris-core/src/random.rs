@@ -0,0 +1,147 @@
+//! The random generator of the game.
+//!
+//! It is the ChaCha20 stream cipher keyed with a 32-byte seed, and it produces exactly the same
+//! numbers as `StdRng::from_seed` of `rand` 0.7, which the interactor used originally. It is
+//! implemented here so the tetromino sequences of the tests do not depend on the versions of the
+//! dependencies or the toolchain. Any change to it changes the sequences of all the tests, which
+//! is guarded by the golden tests of the interactor.
+
+const BLOCK_WORDS: usize = 16;
+
+pub struct Random {
+    key: [u32; 8],
+    /// The nonce of ChaCha20, which gives independent streams for the same seed.
+    stream: u64,
+    counter: u64,
+    block: [u32; BLOCK_WORDS],
+    index: usize,
+}
+
+impl Random {
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self::from_seed_and_stream(seed, 0)
+    }
+
+    /// The generator for the seed whose numbers do not depend on the numbers of the other streams
+    /// (the same as `ChaChaRng::set_stream` of `rand_chacha`).
+    pub fn from_seed_and_stream(seed: [u8; 32], stream: u64) -> Self {
+        let mut key = [0; 8];
+        for (word, bytes) in key.iter_mut().zip(seed.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        Self {
+            key,
+            stream,
+            counter: 0,
+            block: [0; BLOCK_WORDS],
+            index: BLOCK_WORDS,
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        if self.index >= BLOCK_WORDS {
+            self.generate_block();
+        }
+        let value = self.block[self.index];
+        self.index += 1;
+        value
+    }
+
+    /// A uniformly distributed index in `0..len` (the same as `SliceRandom::choose` of `rand`
+    /// 0.7 does it).
+    pub fn gen_index(&mut self, len: usize) -> usize {
+        assert!(len > 0 && len <= u32::MAX as usize);
+        let range = len as u32;
+        let zone = (range << range.leading_zeros()).wrapping_sub(1);
+        loop {
+            let value = u64::from(self.next_u32()) * u64::from(range);
+            if value as u32 <= zone {
+                return (value >> 32) as usize;
+            }
+        }
+    }
+
+    fn generate_block(&mut self) {
+        let mut state = [
+            0x6170_7865,
+            0x3320_646e,
+            0x7962_2d32,
+            0x6b20_6574,
+            self.key[0],
+            self.key[1],
+            self.key[2],
+            self.key[3],
+            self.key[4],
+            self.key[5],
+            self.key[6],
+            self.key[7],
+            self.counter as u32,
+            (self.counter >> 32) as u32,
+            self.stream as u32,
+            (self.stream >> 32) as u32,
+        ];
+        let initial_state = state;
+        for _ in 0..10 {
+            quarter_round(&mut state, 0, 4, 8, 12);
+            quarter_round(&mut state, 1, 5, 9, 13);
+            quarter_round(&mut state, 2, 6, 10, 14);
+            quarter_round(&mut state, 3, 7, 11, 15);
+            quarter_round(&mut state, 0, 5, 10, 15);
+            quarter_round(&mut state, 1, 6, 11, 12);
+            quarter_round(&mut state, 2, 7, 8, 13);
+            quarter_round(&mut state, 3, 4, 9, 14);
+        }
+        for (word, initial_word) in state.iter_mut().zip(initial_state.iter()) {
+            *word = word.wrapping_add(*initial_word);
+        }
+        self.block = state;
+        self.counter = self.counter.wrapping_add(1);
+        self.index = 0;
+    }
+}
+
+fn quarter_round(state: &mut [u32; BLOCK_WORDS], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chacha20_keystream() {
+        // The keystream for the all-zero key and nonce from the ChaCha20 test vectors
+        let mut random = Random::from_seed([0; 32]);
+        let first_words = (0..4).map(|_| random.next_u32()).collect::<Vec<_>>();
+        assert_eq!(
+            first_words,
+            vec![0xade0_b876, 0x903d_f1a0, 0xe56a_5d40, 0x28bd_8653]
+        );
+
+        // The stream goes into the nonce words of the state
+        let mut random = Random::from_seed_and_stream([0; 32], 1);
+        assert_eq!(random.next_u32(), 0xd6df_3fef);
+    }
+
+    #[test]
+    fn test_gen_index() {
+        let mut random = Random::from_seed(*b"KhCup XVII 2020 Tetris interacto");
+        let mut hits = [0; 7];
+        for _ in 0..7000 {
+            hits[random.gen_index(7)] += 1;
+        }
+        assert!(
+            hits.iter().all(|&hit| hit > 800 && hit < 1200),
+            "{:?}",
+            hits
+        );
+        assert!((0..100).all(|_| random.gen_index(1) == 0));
+    }
+}
@@ -0,0 +1,195 @@
+//! The rotation systems. The `rotate` action always turns the tetromino clockwise by 90 degrees,
+//! and the rotation system decides where the rotated tetromino ends up.
+
+use crate::{Action, Tetromino, TetrominoKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, derive_more::Display)]
+pub enum RotationSystem {
+    /// The leftmost column of the tetromino stays, and the tetromino is moved to the left when it
+    /// would stick out of the right wall.
+    #[default]
+    #[display(fmt = "classic")]
+    Classic,
+    /// The Super Rotation System of the guideline games: the tetrominoes rotate around their
+    /// centers, and the wall kicks are tried in order until the rotated tetromino fits.
+    #[display(fmt = "srs")]
+    Srs,
+}
+
+crate::impl_from_str!(RotationSystem { Classic, Srs });
+
+/// The SRS state (0 is the spawn state, 1 is R, 2, 3 is L) of the rotation 0 of [`Tetromino`],
+/// since the tetrominoes of the game do not spawn in the SRS spawn state.
+fn srs_base_state(kind: TetrominoKind) -> usize {
+    match kind {
+        TetrominoKind::I | TetrominoKind::L => 1,
+        TetrominoKind::T => 2,
+        TetrominoKind::J => 3,
+        TetrominoKind::O | TetrominoKind::S | TetrominoKind::Z => 0,
+    }
+}
+
+/// The `(column, line)` of the top left corner of the blocks within the SRS bounding box for
+/// every state (lines are counted downwards).
+fn srs_corners(kind: TetrominoKind) -> [(isize, isize); 4] {
+    match kind {
+        TetrominoKind::I => [(0, 1), (2, 0), (0, 2), (1, 0)],
+        TetrominoKind::O => [(0, 0); 4],
+        _ => [(0, 0), (1, 0), (0, 1), (0, 0)],
+    }
+}
+
+/// The wall kicks of the clockwise rotation from every state as `(column, line)` offsets, where
+/// lines are counted upwards (as in the SRS tables).
+fn srs_kicks(kind: TetrominoKind) -> &'static [[(isize, isize); 5]; 4] {
+    match kind {
+        TetrominoKind::I => &[
+            [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+            [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+            [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+            [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+        ],
+        TetrominoKind::O => &[[(0, 0); 5]; 4],
+        _ => &[
+            [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+            [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+            [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+            [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+        ],
+    }
+}
+
+impl RotationSystem {
+    /// The places to try for the tetromino rotated clockwise, in order, with the number of lines
+    /// that the tetromino moves up (or down when it is negative). The places to the left of the
+    /// board are skipped.
+    pub fn rotations(self, tetromino: &Tetromino, board_width: usize) -> Vec<(Tetromino, isize)> {
+        match self {
+            Self::Classic => {
+                let mut rotated = *tetromino;
+                rotated.apply_action(Action::Rotate, board_width);
+                vec![(rotated, 0)]
+            }
+            Self::Srs => {
+                let state = (srs_base_state(tetromino.kind) + tetromino.rotation / 90) % 4;
+                let corners = srs_corners(tetromino.kind);
+                let (from_column, from_line) = corners[state];
+                let (to_column, to_line) = corners[(state + 1) % 4];
+                srs_kicks(tetromino.kind)[state]
+                    .iter()
+                    .filter_map(|&(kick_column, kick_line)| {
+                        let position =
+                            tetromino.position as isize + to_column - from_column + kick_column;
+                        if position < 1 {
+                            return None;
+                        }
+                        let rotated = Tetromino {
+                            position: position as usize,
+                            rotation: tetromino.rotation + 90,
+                            ..*tetromino
+                        };
+                        Some((rotated, kick_line - (to_line - from_line)))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Tetromino {
+    /// Rotates the tetromino above the stack, where only the walls can block it (the lines that
+    /// the wall kicks move the tetromino by do not matter there), and tells whether it has
+    /// rotated.
+    pub fn rotate(&mut self, rotation_system: RotationSystem, board_width: usize) -> bool {
+        match rotation_system
+            .rotations(self, board_width)
+            .into_iter()
+            .find(|(rotated, _)| rotated.position + rotated.width() - 1 <= board_width)
+        {
+            Some((rotated, _)) => {
+                *self = rotated;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srs_rotation_centers() {
+        // Four rotations without kicks bring every tetromino back to the same place
+        for &kind in &TetrominoKind::ALL {
+            for &rotation in &[0, 90, 180, 270] {
+                let tetromino = Tetromino {
+                    kind,
+                    position: 4,
+                    rotation,
+                };
+                let mut rotated = tetromino;
+                let mut lines = 0;
+                for _ in 0..4 {
+                    let (next_rotated, line_shift) = RotationSystem::Srs.rotations(&rotated, 10)[0];
+                    rotated = next_rotated;
+                    lines += line_shift;
+                }
+                assert_eq!(rotated.position, tetromino.position, "{:?}", tetromino);
+                assert_eq!(
+                    rotated.rotation % 360,
+                    tetromino.rotation,
+                    "{:?}",
+                    tetromino
+                );
+                assert_eq!(lines, 0, "{:?}", tetromino);
+            }
+        }
+    }
+
+    #[test]
+    fn test_srs_wall_kicks() {
+        for &(kind, position, rotation, expected_position) in &[
+            // The vertical I turns around its center...
+            (TetrominoKind::I, 5, 0, 3),
+            // ...unless the walls kick it back into the board
+            (TetrominoKind::I, 1, 0, 1),
+            (TetrominoKind::I, 10, 0, 7),
+            (TetrominoKind::T, 1, 90, 1),
+            (TetrominoKind::T, 9, 270, 8),
+            (TetrominoKind::O, 9, 0, 9),
+        ] {
+            let mut tetromino = Tetromino {
+                kind,
+                position,
+                rotation,
+            };
+            assert!(tetromino.rotate(RotationSystem::Srs, 10));
+            assert_eq!(
+                (tetromino.position, tetromino.rotation),
+                (expected_position, rotation + 90),
+                "{} {} {}",
+                kind,
+                position,
+                rotation
+            );
+        }
+
+        let mut tetromino = Tetromino {
+            kind: TetrominoKind::I,
+            position: 10,
+            rotation: 0,
+        };
+        assert!(tetromino.rotate(RotationSystem::Classic, 10));
+        assert_eq!(tetromino.position, 7);
+    }
+
+    #[test]
+    fn test_parse_rotation_system() {
+        for &rotation_system in &[RotationSystem::Classic, RotationSystem::Srs] {
+            assert_eq!(rotation_system.to_string().parse(), Ok(rotation_system));
+        }
+        assert_eq!("nrs".parse::<RotationSystem>(), Err(()));
+    }
+}
@@ -0,0 +1,169 @@
+//! Guideline-style scoring: the more lines a tetromino clears at once the more points it gets, and
+//! there are bonuses for combos (the consecutive tetrominoes that clear lines), back-to-back
+//! tetrises, and perfect clears (when the board is empty after the clear).
+
+/// What the game needs to reach to be won.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, derive_more::Display)]
+pub enum Goal {
+    /// The number of the cleared lines.
+    #[default]
+    #[display(fmt = "lines")]
+    Lines,
+    /// The points of [`Scoring`].
+    #[display(fmt = "score")]
+    Score,
+}
+
+crate::impl_from_str!(Goal { Lines, Score });
+
+/// When the game ends besides the death.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, derive_more::Display)]
+pub enum Mode {
+    /// The game ends as soon as it reaches the goal (or runs out of the tetrominoes when there is
+    /// a limit of them).
+    #[default]
+    #[display(fmt = "marathon")]
+    Marathon,
+    /// As many lines (or points) as possible within the limit of the tetrominoes: the game goes
+    /// on after it reaches the goal until it runs out of the tetrominoes.
+    #[display(fmt = "ultra")]
+    Ultra,
+    /// The lines of the goal within as few tetrominoes as possible: the game ends as soon as it
+    /// reaches the goal, and the limit of the tetrominoes is required. The game that has reached
+    /// the goal is graded by its tetrominoes against [`crate::Game::sprint_par`].
+    #[display(fmt = "sprint")]
+    Sprint,
+}
+
+crate::impl_from_str!(Mode {
+    Marathon,
+    Ultra,
+    Sprint
+});
+
+impl Mode {
+    /// Whether the game needs a limit of the tetrominoes to end.
+    pub fn requires_piece_limit(self) -> bool {
+        self != Self::Marathon
+    }
+
+    /// Whether the game ends as soon as it reaches the goal.
+    pub fn ends_at_goal(self) -> bool {
+        self != Self::Ultra
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Scoring {
+    pub points: u64,
+    /// The number of the consecutive tetrominoes that have cleared lines.
+    consecutive_clears: u64,
+    /// Whether the last clear was a tetris.
+    last_clear_was_tetris: bool,
+}
+
+impl Scoring {
+    pub const COMBO_POINTS: u64 = 50;
+
+    /// The points for clearing 1, 2, 3, and 4 lines at once.
+    fn clear_points(cleared_lines: u64) -> u64 {
+        match cleared_lines {
+            1 => 100,
+            2 => 300,
+            3 => 500,
+            _ => 800,
+        }
+    }
+
+    /// The bonus for clearing 1, 2, 3, and 4 lines at once when the board gets empty.
+    fn perfect_clear_points(cleared_lines: u64, is_back_to_back: bool) -> u64 {
+        match cleared_lines {
+            1 => 800,
+            2 => 1200,
+            3 => 1800,
+            _ if is_back_to_back => 3200,
+            _ => 2000,
+        }
+    }
+
+    /// Scores the tetromino that has just locked and cleared the lines (if any), and returns the
+    /// points that it has got.
+    pub fn record(&mut self, cleared_lines: u64, is_perfect_clear: bool) -> u64 {
+        if cleared_lines == 0 {
+            self.consecutive_clears = 0;
+            return 0;
+        }
+        let is_tetris = cleared_lines >= 4;
+        let is_back_to_back = is_tetris && self.last_clear_was_tetris;
+
+        let mut points = Self::clear_points(cleared_lines);
+        if is_back_to_back {
+            points += points / 2;
+        }
+        points += Self::COMBO_POINTS * self.consecutive_clears;
+        if is_perfect_clear {
+            points += Self::perfect_clear_points(cleared_lines, is_back_to_back);
+        }
+
+        self.consecutive_clears += 1;
+        self.last_clear_was_tetris = is_tetris;
+        self.points += points;
+        points
+    }
+
+    /// The combo counter: 0 for the first of the consecutive clears, 1 for the second one, etc.
+    pub fn combo(&self) -> Option<u64> {
+        self.consecutive_clears.checked_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoring() {
+        let mut scoring = Scoring::default();
+        for &(cleared_lines, is_perfect_clear, points, combo) in &[
+            (0, false, 0, None),
+            (1, false, 100, Some(0)),
+            (2, false, 350, Some(1)),
+            (0, false, 0, None),
+            (4, false, 800, Some(0)),
+            (0, false, 0, None),
+            // Back-to-back tetrises are not broken by the tetrominoes that do not clear lines
+            (4, false, 1200, Some(0)),
+            (4, false, 1250, Some(1)),
+            // ...but they are broken by the other clears
+            (3, false, 600, Some(2)),
+            (4, false, 950, Some(3)),
+            (1, true, 1100, Some(4)),
+        ] {
+            assert_eq!(
+                scoring.record(cleared_lines, is_perfect_clear),
+                points,
+                "{} {}",
+                cleared_lines,
+                is_perfect_clear
+            );
+            assert_eq!(scoring.combo(), combo);
+        }
+        assert_eq!(scoring.points, 6350);
+
+        let mut scoring = Scoring::default();
+        scoring.record(4, true);
+        assert_eq!(scoring.record(4, true), 1200 + 50 + 3200);
+    }
+
+    #[test]
+    fn test_parse_goal_and_mode() {
+        for &goal in &[Goal::Lines, Goal::Score] {
+            assert_eq!(goal.to_string().parse(), Ok(goal));
+        }
+        assert_eq!("time".parse::<Goal>(), Err(()));
+        for &mode in &[Mode::Marathon, Mode::Ultra, Mode::Sprint] {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+        }
+        assert_eq!("zen".parse::<Mode>(), Err(()));
+    }
+}
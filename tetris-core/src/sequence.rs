@@ -0,0 +1,192 @@
+//! A fixed sequence of the tetrominoes that the game gives out instead of the random ones, e.g.
+//! for the handcrafted tests or to replay a round of the contest exactly:
+//!
+//! ```text
+//! O 4
+//! I 1
+//! T 7
+//! ```
+//!
+//! Every line holds the kind of the tetromino and its initial position. The empty lines and the
+//! lines that start with `#` are skipped.
+
+use crate::{Level, Tetromino, TetrominoKind};
+
+/// What the game does when all the tetrominoes of the sequence have been given out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, derive_more::Display)]
+pub enum SequenceEnd {
+    /// The game ends with [`crate::GameOver::Survived`] (the held tetromino, if any, is not
+    /// played).
+    #[default]
+    #[display(fmt = "stop")]
+    Stop,
+    /// The sequence starts over.
+    #[display(fmt = "repeat")]
+    Repeat,
+    /// The tetrominoes are picked randomly from the seed of the game, as if there were no
+    /// sequence.
+    #[display(fmt = "random")]
+    Random,
+}
+
+crate::impl_from_str!(SequenceEnd {
+    Stop,
+    Repeat,
+    Random
+});
+
+/// The sequence does not fit the level (the lines are counted from 1).
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum SequenceError {
+    #[display(
+        fmt = "line {}: expected a tetromino kind and its position, got {:?}",
+        _0,
+        _1
+    )]
+    InvalidLine(usize, String),
+    #[display(fmt = "the sequence has no tetrominoes")]
+    Empty,
+    #[display(fmt = "line {}: the level has no {} tetrominoes", _0, _1)]
+    UnknownKind(usize, TetrominoKind),
+    #[display(
+        fmt = "line {}: the tetromino does not fit into the board at the position {}",
+        _0,
+        _1
+    )]
+    PositionOutOfBoard(usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence {
+    /// The tetrominoes with the numbers of their lines.
+    pub tetrominoes: Vec<(usize, Tetromino)>,
+    pub end: SequenceEnd,
+}
+
+impl Sequence {
+    /// Parses the lines of the sequence, e.g. `O 4`.
+    pub fn parse<'a>(
+        lines: impl IntoIterator<Item = &'a str>,
+        end: SequenceEnd,
+    ) -> Result<Self, SequenceError> {
+        let mut tetrominoes = Vec::new();
+        for (index, line) in lines.into_iter().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid_line = || SequenceError::InvalidLine(index + 1, line.to_owned());
+            let mut tokens = line.split_ascii_whitespace();
+            let (kind, position) = match (tokens.next(), tokens.next(), tokens.next()) {
+                (Some(kind), Some(position), None) => (kind, position),
+                _ => return Err(invalid_line()),
+            };
+            tetrominoes.push((
+                index + 1,
+                Tetromino {
+                    kind: kind.parse().map_err(|()| invalid_line())?,
+                    position: position.parse().map_err(|_| invalid_line())?,
+                    rotation: 0,
+                },
+            ));
+        }
+        if tetrominoes.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+        Ok(Self { tetrominoes, end })
+    }
+
+    /// Checks that the level has the kinds of the tetrominoes, and that they fit into its board.
+    pub fn validate(&self, level: &Level) -> Result<(), SequenceError> {
+        for &(line_number, tetromino) in &self.tetrominoes {
+            if !level.tetromino_kinds.contains(&tetromino.kind) {
+                return Err(SequenceError::UnknownKind(line_number, tetromino.kind));
+            }
+            if tetromino.position < 1
+                || tetromino.position + tetromino.width() - 1 > level.board_width
+            {
+                return Err(SequenceError::PositionOutOfBoard(
+                    line_number,
+                    tetromino.position,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tetrominoes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tetrominoes.is_empty()
+    }
+
+    /// The tetromino with the given index (starting with 0) if the sequence has it (the repeated
+    /// sequence has all of them).
+    pub fn get(&self, index: usize) -> Option<Tetromino> {
+        match self.end {
+            SequenceEnd::Repeat => Some(self.tetrominoes[index % self.len()].1),
+            SequenceEnd::Stop | SequenceEnd::Random => {
+                self.tetrominoes.get(index).map(|&(_, tetromino)| tetromino)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sequence() {
+        let sequence = Sequence::parse(
+            "# The round\nO 4\n\nI 1\r\nT 8\n".lines(),
+            SequenceEnd::Repeat,
+        )
+        .unwrap();
+        assert_eq!(
+            sequence
+                .tetrominoes
+                .iter()
+                .map(|&(line_number, tetromino)| (line_number, tetromino.kind, tetromino.position))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, TetrominoKind::O, 4),
+                (4, TetrominoKind::I, 1),
+                (5, TetrominoKind::T, 8)
+            ]
+        );
+        assert_eq!(
+            sequence.get(4).map(|tetromino| tetromino.kind),
+            Some(TetrominoKind::I)
+        );
+        assert_eq!(sequence.validate(&Level::builtin(9).unwrap()), Ok(()));
+        assert_eq!(
+            sequence.validate(&Level::builtin(0).unwrap()),
+            Err(SequenceError::UnknownKind(4, TetrominoKind::I))
+        );
+
+        let sequence = Sequence::parse(vec!["O 10"], SequenceEnd::Stop).unwrap();
+        assert_eq!(sequence.get(1), None);
+        assert_eq!(
+            sequence.validate(&Level::builtin(9).unwrap()),
+            Err(SequenceError::PositionOutOfBoard(1, 10))
+        );
+
+        assert_eq!(
+            Sequence::parse(vec!["O"], SequenceEnd::Stop),
+            Err(SequenceError::InvalidLine(1, "O".into()))
+        );
+        assert_eq!(
+            Sequence::parse(vec!["O 1", "Q 1"], SequenceEnd::Stop),
+            Err(SequenceError::InvalidLine(2, "Q 1".into()))
+        );
+        assert_eq!(
+            Sequence::parse(vec!["# nothing"], SequenceEnd::Stop),
+            Err(SequenceError::Empty)
+        );
+        assert_eq!("repeat".parse(), Ok(SequenceEnd::Repeat));
+        assert_eq!("loop".parse::<SequenceEnd>(), Err(()));
+    }
+}
@@ -0,0 +1,96 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum TetrominoKind {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    J,
+    L,
+}
+
+impl TetrominoKind {
+    pub const ALL: [TetrominoKind; 7] = [
+        TetrominoKind::I,
+        TetrominoKind::O,
+        TetrominoKind::T,
+        TetrominoKind::S,
+        TetrominoKind::Z,
+        TetrominoKind::J,
+        TetrominoKind::L,
+    ];
+}
+
+impl std::str::FromStr for TetrominoKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "I" => Self::I,
+            "O" => Self::O,
+            "T" => Self::T,
+            "S" => Self::S,
+            "Z" => Self::Z,
+            "J" => Self::J,
+            "L" => Self::L,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// A tetromino as it is seen by the game: `position` is the 1-based column of its leftmost
+/// block, and `rotation` is measured in degrees (any multiple of 90 is accepted).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tetromino {
+    pub kind: TetrominoKind,
+    pub position: usize,
+    pub rotation: usize,
+}
+
+impl Tetromino {
+    /// Blocks as `(column, line)` offsets, where lines are counted from the top of the tetromino
+    /// downwards.
+    pub fn blocks(&self) -> &'static [(usize, usize)] {
+        match self.kind {
+            TetrominoKind::I => match self.rotation % 180 {
+                0 => &[(0, 0), (0, 1), (0, 2), (0, 3)],
+                _ => &[(0, 0), (1, 0), (2, 0), (3, 0)],
+            },
+            TetrominoKind::O => &[(0, 0), (1, 0), (0, 1), (1, 1)],
+            TetrominoKind::T => match self.rotation % 360 {
+                0 => &[(0, 0), (1, 0), (2, 0), (1, 1)],
+                90 => &[(1, 0), (1, 1), (1, 2), (0, 1)],
+                180 => &[(0, 1), (1, 1), (2, 1), (1, 0)],
+                _ => &[(0, 0), (0, 1), (0, 2), (1, 1)],
+            },
+            TetrominoKind::S => match self.rotation % 180 {
+                0 => &[(0, 1), (1, 1), (1, 0), (2, 0)],
+                _ => &[(0, 0), (0, 1), (1, 1), (1, 2)],
+            },
+            TetrominoKind::Z => match self.rotation % 180 {
+                0 => &[(0, 0), (1, 0), (1, 1), (2, 1)],
+                _ => &[(1, 0), (1, 1), (0, 1), (0, 2)],
+            },
+            TetrominoKind::J => match self.rotation % 360 {
+                0 => &[(1, 0), (1, 1), (1, 2), (0, 2)],
+                90 => &[(0, 0), (0, 1), (1, 1), (2, 1)],
+                180 => &[(0, 0), (0, 1), (0, 2), (1, 0)],
+                _ => &[(0, 0), (1, 0), (2, 0), (2, 1)],
+            },
+            TetrominoKind::L => match self.rotation % 360 {
+                0 => &[(0, 0), (0, 1), (0, 2), (1, 2)],
+                90 => &[(0, 0), (1, 0), (2, 0), (0, 1)],
+                180 => &[(0, 0), (1, 0), (1, 1), (1, 2)],
+                _ => &[(0, 1), (1, 1), (2, 1), (2, 0)],
+            },
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.blocks().iter().map(|(x, _)| x).max().unwrap_or(&0) + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.blocks().iter().map(|(_, y)| y).max().unwrap_or(&0) + 1
+    }
+}
@@ -0,0 +1,103 @@
+//! The verdict of a game, which the interactor reports with its exit code and the result line,
+//! and the tetris-runner reads back.
+
+use std::time::Duration;
+
+use crate::exit_code;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum Verdict {
+    #[display(fmt = "ok")]
+    Ok,
+    #[display(fmt = "wrong answer")]
+    WrongAnswer,
+    #[display(fmt = "wrong output format")]
+    PresentationError,
+    /// testlib has no exit code for the time limit, so it is reported as the wrong answer, and
+    /// only the result line tells them apart.
+    #[display(fmt = "time limit exceeded")]
+    TimeLimitExceeded,
+    /// The percent of the points for the test.
+    #[display(fmt = "points {}", _0)]
+    Partial(u8),
+}
+
+impl Verdict {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Ok => exit_code::OK,
+            Self::WrongAnswer | Self::TimeLimitExceeded => exit_code::WA,
+            Self::PresentationError => exit_code::PE,
+            Self::Partial(percent) => exit_code::PARTIAL_BASE + i32::from(percent),
+        }
+    }
+
+    /// The verdict that the interactor has reported with the exit code and the result line (none
+    /// for an exit code that is not a verdict, e.g. a crash of the interactor).
+    pub fn from_result(exit_code: i32, result: &str) -> Option<Self> {
+        Some(match exit_code {
+            exit_code::OK => Self::Ok,
+            exit_code::WA if result.starts_with(&Self::TimeLimitExceeded.to_string()) => {
+                Self::TimeLimitExceeded
+            }
+            exit_code::WA => Self::WrongAnswer,
+            exit_code::PE => Self::PresentationError,
+            code if (exit_code::PARTIAL_BASE..=exit_code::PARTIAL_BASE + 100).contains(&code) => {
+                Self::Partial((code - exit_code::PARTIAL_BASE) as u8)
+            }
+            _ => return None,
+        })
+    }
+
+    /// The points of the test that the verdict gets.
+    pub fn points(self, test_points: u32) -> u32 {
+        match self {
+            Self::Ok => test_points,
+            Self::Partial(percent) => test_points * u32::from(percent) / 100,
+            _ => 0,
+        }
+    }
+}
+
+/// Parses a positive number of seconds of a time limit (e.g. `0.5`).
+pub fn parse_seconds(seconds: &str) -> Result<Duration, String> {
+    match seconds.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds > 0.0 => Ok(Duration::from_secs_f64(seconds)),
+        _ => Err(format!("invalid number of seconds {:?}", seconds)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verdict() {
+        for &(verdict, exit_code, points) in &[
+            (Verdict::Ok, 0, 10),
+            (Verdict::WrongAnswer, 1, 0),
+            (Verdict::PresentationError, 2, 0),
+            (Verdict::TimeLimitExceeded, 1, 0),
+            (Verdict::Partial(45), 61, 4),
+            (Verdict::Partial(100), 116, 10),
+        ] {
+            assert_eq!(verdict.exit_code(), exit_code, "{}", verdict);
+            let result = format!("{} Dead. Lines: 5. Score: 500. Tetrominoes: 40", verdict);
+            assert_eq!(Verdict::from_result(exit_code, &result), Some(verdict));
+            assert_eq!(verdict.points(10), points, "{}", verdict);
+        }
+        assert_eq!(Verdict::Partial(45).to_string(), "points 45");
+        // The result line is not written when the interactor is run by hand
+        assert_eq!(Verdict::from_result(1, ""), Some(Verdict::WrongAnswer));
+        assert_eq!(Verdict::from_result(5, ""), None);
+        assert_eq!(Verdict::from_result(117, ""), None);
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_seconds("0.5"), Ok(Duration::from_millis(500)));
+        assert!(parse_seconds("0").is_err());
+        assert!(parse_seconds("-1").is_err());
+        assert!(parse_seconds("inf").is_err());
+    }
+}
@@ -0,0 +1,465 @@
+//! The answer file of a test:
+//!
+//! ```text
+//! 1000 01
+//! level 9
+//! width 6
+//! randomizer bag
+//! preview 2
+//! hold true
+//! gravity step
+//! rotation srs
+//! goal score
+//! mode ultra
+//! pieces 500
+//! garbage 10 2
+//! grading tiers 1000:20 5000:60
+//! feedback landing
+//! board
+//! |          |
+//! |#  ##  ## |
+//! |## #### ##|
+//! sequence repeat
+//! O 4
+//! I 1
+//! ```
+//!
+//! The first line holds the goal, which is the number of lines to clean or the score depending
+//! on the `goal` setting (followed by anything that makes the seed unique), and its raw bytes
+//! seed the random generator. The following lines are optional `<setting> <value>` pairs, which
+//! do not affect the seed. The `board` setting is followed by the rows that the board starts with
+//! at the bottom in the format of the board display (the bottom line `____` is optional), and the
+//! `sequence` setting (with the optional end of the sequence) is followed by the tetrominoes that
+//! the game gives out instead of the random ones (see [`tetris_core::Sequence`]; the blank lines
+//! and the lines that start with `#` are skipped there).
+
+use crate::feedback::Feedback;
+use crate::verdict::Grading;
+use tetris_core::{
+    BoardError, Garbage, Goal, Gravity, Level, Mode, Randomizer, RotationSystem, Sequence,
+    SequenceError, TetrisBoard, TetrisBoardLine, TetrominoKind,
+};
+
+#[derive(Debug, PartialEq, Eq, derive_more::Display)]
+pub enum AnswerError {
+    #[display(fmt = "the goal is missing")]
+    MissingScoreLimit,
+    #[display(fmt = "the goal is invalid: {:?}", _0)]
+    InvalidScoreLimit(String),
+    #[display(fmt = "unknown setting: {:?}", _0)]
+    UnknownSetting(String),
+    #[display(
+        fmt = "the `board` setting takes no value (the rows follow it), got {:?}",
+        _0
+    )]
+    BoardWithValue(String),
+    #[display(fmt = "invalid value of the setting {:?}: {:?}", _0, _1)]
+    InvalidSettingValue(String, String),
+    #[display(fmt = "the {} mode requires the `pieces` setting", _0)]
+    MissingPieceLimit(Mode),
+    #[display(fmt = "the sprint mode requires the lines goal")]
+    SprintScoreGoal,
+    #[display(fmt = "the starting board is invalid: {}", _0)]
+    InvalidBoard(BoardError),
+    #[display(fmt = "the sequence is invalid: {}", _0)]
+    InvalidSequence(SequenceError),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Answer {
+    pub seed: [u8; 32],
+    pub score_limit: u64,
+    pub level: Option<u8>,
+    pub board_width: Option<usize>,
+    pub board_height: Option<usize>,
+    pub randomizer: Option<Randomizer>,
+    pub preview: Option<usize>,
+    pub allows_hold: Option<bool>,
+    pub gravity: Option<Gravity>,
+    pub rotation_system: Option<RotationSystem>,
+    pub goal: Option<Goal>,
+    pub mode: Option<Mode>,
+    pub piece_limit: Option<u64>,
+    pub garbage: Option<Garbage>,
+    /// How the games that have not reached the goal are graded.
+    pub grading: Grading,
+    /// What the interactor sends back after every locked tetromino.
+    pub feedback: Option<Feedback>,
+    /// The lines that the board starts with at the bottom (from the bottom to the top).
+    pub starting_lines: Vec<TetrisBoardLine>,
+    /// The tetrominoes that the game gives out instead of the random ones.
+    pub sequence: Option<Sequence>,
+}
+
+impl std::str::FromStr for Answer {
+    type Err = AnswerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.split_inclusive('\n').peekable();
+        let seed_line = lines.next().unwrap_or_default();
+
+        let mut seed = [0; 32];
+        let seed_len = seed.len().min(seed_line.len());
+        seed[..seed_len].copy_from_slice(&seed_line.as_bytes()[..seed_len]);
+
+        let score_limit = seed_line
+            .split_ascii_whitespace()
+            .next()
+            .ok_or(AnswerError::MissingScoreLimit)?;
+        let score_limit = score_limit
+            .parse()
+            .map_err(|_| AnswerError::InvalidScoreLimit(score_limit.to_owned()))?;
+
+        let mut answer = Self {
+            seed,
+            score_limit,
+            level: None,
+            board_width: None,
+            board_height: None,
+            randomizer: None,
+            preview: None,
+            allows_hold: None,
+            gravity: None,
+            rotation_system: None,
+            goal: None,
+            mode: None,
+            piece_limit: None,
+            garbage: None,
+            grading: Grading::default(),
+            feedback: None,
+            starting_lines: Vec::new(),
+            sequence: None,
+        };
+        while let Some(line) = lines.next() {
+            let mut tokens = line.split_ascii_whitespace();
+            let setting = match tokens.next() {
+                Some(setting) => setting,
+                None => continue,
+            };
+            let value = tokens.collect::<Vec<_>>().join(" ");
+            let invalid_value = || AnswerError::InvalidSettingValue(setting.into(), value.clone());
+            let board_size = || match value.parse() {
+                Ok(size) if size >= TetrisBoard::MIN_SIZE => Ok(size),
+                _ => Err(invalid_value()),
+            };
+            match setting {
+                "level" => answer.level = Some(value.parse().map_err(|_| invalid_value())?),
+                "width" => answer.board_width = Some(board_size()?),
+                "height" => answer.board_height = Some(board_size()?),
+                "randomizer" => {
+                    answer.randomizer = Some(value.parse().map_err(|()| invalid_value())?)
+                }
+                "preview" => match value.parse() {
+                    Ok(preview) if preview <= Level::MAX_PREVIEW => answer.preview = Some(preview),
+                    _ => return Err(invalid_value()),
+                },
+                "hold" => answer.allows_hold = Some(value.parse().map_err(|_| invalid_value())?),
+                "gravity" => answer.gravity = Some(value.parse().map_err(|()| invalid_value())?),
+                "rotation" => {
+                    answer.rotation_system = Some(value.parse().map_err(|()| invalid_value())?)
+                }
+                "goal" => answer.goal = Some(value.parse().map_err(|()| invalid_value())?),
+                "mode" => answer.mode = Some(value.parse().map_err(|()| invalid_value())?),
+                "pieces" => match value.parse() {
+                    Ok(piece_limit) if piece_limit > 0 => answer.piece_limit = Some(piece_limit),
+                    _ => return Err(invalid_value()),
+                },
+                "garbage" => answer.garbage = Some(value.parse().map_err(|()| invalid_value())?),
+                "grading" => answer.grading = value.parse().map_err(|_| invalid_value())?,
+                "board" if value.is_empty() => {
+                    let mut rows = Vec::new();
+                    while let Some(row) = lines.next_if(|line| line.trim().starts_with('|')) {
+                        rows.push(row.trim());
+                    }
+                    lines.next_if(|line| line.trim().starts_with('_'));
+                    if rows.is_empty() {
+                        return Err(invalid_value());
+                    }
+                    answer.starting_lines =
+                        TetrisBoard::parse_lines(rows).map_err(AnswerError::InvalidBoard)?;
+                }
+                "board" => return Err(AnswerError::BoardWithValue(value)),
+                "sequence" => {
+                    let end = match value.as_str() {
+                        "" => Default::default(),
+                        end => end.parse().map_err(|()| invalid_value())?,
+                    };
+                    // The blank lines and the comments are skipped as in the sequence file
+                    let mut tetrominoes = Vec::new();
+                    while let Some(tetromino) = lines.next_if(|line| {
+                        line.split_ascii_whitespace().next().map_or(true, |kind| {
+                            kind.starts_with('#') || kind.parse::<TetrominoKind>().is_ok()
+                        })
+                    }) {
+                        tetrominoes.push(tetromino);
+                    }
+                    answer.sequence = Some(
+                        Sequence::parse(tetrominoes, end).map_err(AnswerError::InvalidSequence)?,
+                    );
+                }
+                "feedback" => answer.feedback = Some(value.parse().map_err(|()| invalid_value())?),
+                _ => return Err(AnswerError::UnknownSetting(setting.into())),
+            }
+        }
+        match answer.mode {
+            Some(mode) if mode.requires_piece_limit() && answer.piece_limit.is_none() => {
+                Err(AnswerError::MissingPieceLimit(mode))
+            }
+            _ => Ok(answer),
+        }
+    }
+}
+
+impl Answer {
+    /// Overrides the rules of the level with the settings of the test, and checks that the
+    /// starting board fits the level.
+    pub fn configure_level(&self, level: &mut Level) -> Result<(), AnswerError> {
+        if let Some(board_width) = self.board_width {
+            level.board_width = board_width;
+        }
+        if let Some(board_height) = self.board_height {
+            level.board_height = board_height;
+        }
+        if let Some(randomizer) = self.randomizer {
+            level.randomizer = randomizer;
+        }
+        if let Some(preview) = self.preview {
+            level.preview = preview;
+        }
+        if let Some(allows_hold) = self.allows_hold {
+            level.allows_hold = allows_hold;
+        }
+        if let Some(gravity) = self.gravity {
+            level.gravity = gravity;
+        }
+        if let Some(rotation_system) = self.rotation_system {
+            level.rotation_system = rotation_system;
+        }
+        if let Some(goal) = self.goal {
+            level.goal = goal;
+        }
+        if let Some(mode) = self.mode {
+            level.mode = mode;
+        }
+        if let Some(piece_limit) = self.piece_limit {
+            level.piece_limit = Some(piece_limit);
+        }
+        // The par of the sprint is only known for the lines
+        if level.mode == Mode::Sprint && level.goal != Goal::Lines {
+            return Err(AnswerError::SprintScoreGoal);
+        }
+        if let Some(garbage) = &self.garbage {
+            level.garbage = Some(garbage.clone());
+        }
+        if !self.starting_lines.is_empty() {
+            TetrisBoard::with_lines(level.board_width, level.board_height, &self.starting_lines)
+                .map_err(AnswerError::InvalidBoard)?;
+            level.starting_lines = self.starting_lines.clone();
+        }
+        if let Some(sequence) = &self.sequence {
+            sequence
+                .validate(level)
+                .map_err(AnswerError::InvalidSequence)?;
+            level.sequence = Some(sequence.clone());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetris_core::SequenceEnd;
+
+    #[test]
+    fn test_seed_line() {
+        let answer: Answer = "1000 01\n".parse().unwrap();
+        assert_eq!(answer.score_limit, 1000);
+        assert_eq!(&answer.seed[..8], b"1000 01\n");
+        assert!(answer.seed[8..].iter().all(|&byte| byte == 0));
+        assert_eq!(answer.level, None);
+
+        let long_answer: Answer = "10 0123456789012345678901234567890123456789"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            &long_answer.seed[..],
+            &b"10 01234567890123456789012345678"[..]
+        );
+    }
+
+    #[test]
+    fn test_settings_do_not_affect_seed() {
+        let answer: Answer = "1000 01\nlevel 9\n".parse().unwrap();
+        assert_eq!(answer.level, Some(9));
+        assert_eq!(answer.seed, "1000 01\n".parse::<Answer>().unwrap().seed);
+
+        let answer: Answer = "100 narrow\nwidth 6\nheight 40\n".parse().unwrap();
+        assert_eq!(answer.board_width, Some(6));
+        assert_eq!(answer.board_height, Some(40));
+        assert_eq!(answer.randomizer, None);
+
+        let answer: Answer =
+            "100 bag\nrandomizer bag\npreview 2\nhold true\ngravity step\nrotation srs\ngoal score\nmode ultra\npieces 500\ngarbage at 5:1 20:3\ngrading proportional\nfeedback board\n"
+                .parse()
+                .unwrap();
+        assert_eq!(answer.randomizer, Some(Randomizer::Bag));
+        assert_eq!(answer.preview, Some(2));
+        let mut level = Level::default();
+        answer.configure_level(&mut level).unwrap();
+        assert_eq!(level.randomizer, Randomizer::Bag);
+        assert_eq!(level.preview, 2);
+        assert!(level.allows_hold);
+        assert_eq!(level.gravity, Gravity::Step);
+        assert_eq!(level.rotation_system, RotationSystem::Srs);
+        assert_eq!(level.goal, Goal::Score);
+        assert_eq!(level.mode, Mode::Ultra);
+        assert_eq!(level.piece_limit, Some(500));
+        assert_eq!(
+            level.garbage,
+            Some(Garbage::Schedule(vec![(5, 1), (20, 3)]))
+        );
+        assert_eq!(answer.grading, Grading::Proportional);
+        assert_eq!(answer.feedback, Some(Feedback::Board));
+        assert_eq!(level.board_width, TetrisBoard::DEFAULT_WIDTH);
+    }
+
+    #[test]
+    fn test_starting_board() {
+        let answer: Answer = "100 puzzle\nboard\n|      |\n| ## # |\n|## ###|\n______\nwidth 6\n"
+            .parse()
+            .unwrap();
+        assert_eq!(answer.board_width, Some(6));
+        assert_eq!(
+            answer.starting_lines,
+            TetrisBoard::parse_lines(vec!["|      |", "| ## # |", "|## ###|"]).unwrap()
+        );
+        let mut level = Level::default();
+        answer.configure_level(&mut level).unwrap();
+        assert_eq!(level.new_board().to_string().matches('#').count(), 8);
+
+        let mut level = Level::default();
+        assert_eq!(
+            "100 wide\nboard\n|## ###|\n"
+                .parse::<Answer>()
+                .unwrap()
+                .configure_level(&mut level),
+            Err(AnswerError::InvalidBoard(BoardError::InvalidWidth(
+                "|## ###|".into(),
+                TetrisBoard::DEFAULT_WIDTH
+            )))
+        );
+    }
+
+    #[test]
+    fn test_sequence() {
+        let answer: Answer = "10 round\nsequence random\nO 4\nO 1\nlevel 0\n"
+            .parse()
+            .unwrap();
+        assert_eq!(answer.level, Some(0));
+        let sequence = answer.sequence.as_ref().unwrap();
+        assert_eq!(sequence.end, SequenceEnd::Random);
+        assert_eq!(sequence.get(1).map(|tetromino| tetromino.position), Some(1));
+        let mut level = Level::default();
+        answer.configure_level(&mut level).unwrap();
+        assert_eq!(level.sequence.as_ref(), Some(sequence));
+
+        let answer: Answer = "10 round\nsequence\nI 1\n".parse().unwrap();
+        assert_eq!(answer.sequence.as_ref().unwrap().end, SequenceEnd::Stop);
+        assert_eq!(
+            answer.configure_level(&mut Level::default()),
+            Err(AnswerError::InvalidSequence(SequenceError::UnknownKind(
+                1,
+                TetrominoKind::I
+            )))
+        );
+
+        // The blank lines and the comments are skipped
+        let answer: Answer = "10 round\nsequence\n# Round 1\nI 1\n\nO 2\nlevel 0\n"
+            .parse()
+            .unwrap();
+        assert_eq!(answer.sequence.as_ref().unwrap().tetrominoes.len(), 2);
+        assert_eq!(answer.level, Some(0));
+    }
+
+    #[test]
+    fn test_invalid_answer() {
+        assert_eq!("".parse::<Answer>(), Err(AnswerError::MissingScoreLimit));
+        assert_eq!(
+            "many".parse::<Answer>(),
+            Err(AnswerError::InvalidScoreLimit("many".into()))
+        );
+        assert_eq!(
+            "10\nspeed 9".parse::<Answer>(),
+            Err(AnswerError::UnknownSetting("speed".into()))
+        );
+        assert_eq!(
+            "10\nlevel nine".parse::<Answer>(),
+            Err(AnswerError::InvalidSettingValue(
+                "level".into(),
+                "nine".into()
+            ))
+        );
+        assert_eq!(
+            "10\nwidth 3".parse::<Answer>(),
+            Err(AnswerError::InvalidSettingValue("width".into(), "3".into()))
+        );
+        assert_eq!(
+            "10\npreview 1000000000000".parse::<Answer>(),
+            Err(AnswerError::InvalidSettingValue(
+                "preview".into(),
+                "1000000000000".into()
+            ))
+        );
+        assert_eq!(
+            "10\npieces 0".parse::<Answer>(),
+            Err(AnswerError::InvalidSettingValue(
+                "pieces".into(),
+                "0".into()
+            ))
+        );
+        assert_eq!(
+            "10\ngarbage 10 0".parse::<Answer>(),
+            Err(AnswerError::InvalidSettingValue(
+                "garbage".into(),
+                "10 0".into()
+            ))
+        );
+        assert_eq!(
+            "10\nboard\nwidth 6".parse::<Answer>(),
+            Err(AnswerError::InvalidSettingValue("board".into(), "".into()))
+        );
+        assert_eq!(
+            "10\nboard x".parse::<Answer>(),
+            Err(AnswerError::BoardWithValue("x".into()))
+        );
+        assert_eq!(
+            "10\nboard\n|#x# |".parse::<Answer>(),
+            Err(AnswerError::InvalidBoard(BoardError::InvalidRow(
+                "|#x# |".into()
+            )))
+        );
+        assert_eq!(
+            "10\nsequence\nlevel 9".parse::<Answer>(),
+            Err(AnswerError::InvalidSequence(SequenceError::Empty))
+        );
+        assert_eq!(
+            "10\nsequence forever\nO 1".parse::<Answer>(),
+            Err(AnswerError::InvalidSettingValue(
+                "sequence".into(),
+                "forever".into()
+            ))
+        );
+        assert_eq!(
+            "10\nmode sprint".parse::<Answer>(),
+            Err(AnswerError::MissingPieceLimit(Mode::Sprint))
+        );
+        assert_eq!(
+            "10\nmode sprint\npieces 100\nlevel 2"
+                .parse::<Answer>()
+                .unwrap()
+                .configure_level(&mut Level::builtin(2).unwrap()),
+            Err(AnswerError::SprintScoreGoal)
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! The debug protocol mode: after every line of actions that locks the tetromino, the interactor
+//! tells the solution where the tetromino has landed and how many lines it has cleared, so the
+//! solution can check its own copy of the board:
+//!
+//! ```text
+//! landed 4 90 1 0
+//! ```
+//!
+//! The line holds the position and the rotation of the locked tetromino, the lowest row that it
+//! takes (counted from the bottom, starting with 1), and the number of the cleared lines. With
+//! the `board` feedback, the line is followed by the rows of the board after the clearing and the
+//! garbage rows that have risen after the tetromino (from the top to the bottom, `#` for the
+//! occupied cells and `.` for the empty ones), while the garbage itself is announced with the next
+//! tetromino as usual.
+
+use tetris_core::{TetrisBoard, Tetromino};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum Feedback {
+    /// Nothing is sent back (the protocol of the contest).
+    #[default]
+    #[display(fmt = "none")]
+    None,
+    #[display(fmt = "landing")]
+    Landing,
+    /// The landing followed by the whole board.
+    #[display(fmt = "board")]
+    Board,
+}
+
+tetris_core::impl_from_str!(Feedback {
+    None,
+    Landing,
+    Board
+});
+
+impl Feedback {
+    /// The lines to send back after the tetromino has got locked with its top on the given line
+    /// (counted from the bottom, starting with 0).
+    pub fn lines(
+        self,
+        tetromino: &Tetromino,
+        top_line: usize,
+        cleared_lines: u64,
+        board: &TetrisBoard,
+    ) -> Vec<String> {
+        if self == Self::None {
+            return Vec::new();
+        }
+        let mut lines = vec![format!(
+            "landed {} {} {} {}",
+            tetromino.position,
+            tetromino.rotation % 360,
+            top_line + 2 - tetromino.height(),
+            cleared_lines
+        )];
+        if self == Self::Board {
+            lines.extend(board.lines().iter().rev().map(|line| {
+                line.iter()
+                    .map(|cell| if cell.is_occupied() { '#' } else { '.' })
+                    .collect::<String>()
+            }));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetris_core::TetrominoKind;
+
+    #[test]
+    fn test_feedback() {
+        assert_eq!("board".parse(), Ok(Feedback::Board));
+        assert_eq!("rows".parse::<Feedback>(), Err(()));
+
+        let mut board = TetrisBoard::new(4, 4);
+        let tetromino = Tetromino {
+            kind: TetrominoKind::L,
+            position: 2,
+            rotation: 450,
+        };
+        let top_line = board.try_apply_tetromino(tetromino).unwrap();
+        assert_eq!(
+            Feedback::None.lines(&tetromino, top_line, 0, &board),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            Feedback::Landing.lines(&tetromino, top_line, 0, &board),
+            vec!["landed 2 90 1 0"]
+        );
+        assert_eq!(
+            Feedback::Board.lines(&tetromino, top_line, 0, &board),
+            vec!["landed 2 90 1 0", "....", "....", ".###", ".#.."]
+        );
+    }
+}
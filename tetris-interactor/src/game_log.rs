@@ -0,0 +1,155 @@
+//! The game log in the format of tetris-viewer:
+//!
+//! ```text
+//! size 12 24
+//! rotation srs
+//! board 2
+//! |            |
+//! |## #### ####|
+//! O 4
+//! shift_left
+//! I 1
+//! shift_right rotate shift_right
+//! garbage 7
+//!
+//! # Dead. Lines: 0. Score: 0. Tetrominoes: 2
+//! # Response time: total 0.001 s, mean 0.500 ms, max 0.700 ms
+//! ```
+//!
+//! The lines of the log go in this order:
+//!
+//! * `size <width> <height>` when the board is not the default 10x20;
+//! * `rotation <system>` when the rotation system is not the classic one (tetris-viewer only
+//!   draws the classic rotation, so it refuses such logs);
+//! * `board <rows>` followed by the rows of the starting board from the top to the bottom (in the
+//!   format of [`tetris_core::TetrisBoard`]) when the test starts with a non-empty board;
+//! * every tetromino with its kind and its initial position, followed by the line of actions that
+//!   the solution has sent for it, and then by a `garbage <column>` line with the column of the
+//!   hole (starting with 1) for every garbage row that has risen after the tetromino has got
+//!   locked;
+//! * an empty line, which tetris-viewer treats as the end of the game, and the summary, which can
+//!   be followed by more comments.
+
+use std::io::Write;
+
+use tetris_core::{RotationSystem, Tetromino};
+use tracing::{info, warn};
+
+pub struct GameLog<W: Write> {
+    writer: Option<W>,
+}
+
+impl<W: Write> GameLog<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Some(writer),
+        }
+    }
+
+    /// The game log that only goes to the tracing output.
+    pub fn disabled() -> Self {
+        Self { writer: None }
+    }
+
+    /// The width and the height of the board (only logged when they differ from 10x20).
+    pub fn size(&mut self, width: usize, height: usize) {
+        info!(target: "game_log", "size {} {}", width, height);
+        self.write_line(format_args!("size {} {}", width, height));
+    }
+
+    /// The rotation system (only logged when it is not the classic one).
+    pub fn rotation(&mut self, rotation_system: RotationSystem) {
+        info!(target: "game_log", "rotation {}", rotation_system);
+        self.write_line(format_args!("rotation {}", rotation_system));
+    }
+
+    /// The rows of the starting board (from the top to the bottom).
+    pub fn board(&mut self, rows: &[String]) {
+        info!(target: "game_log", "board {}", rows.len());
+        self.write_line(format_args!("board {}", rows.len()));
+        for row in rows {
+            self.write_line(format_args!("{}", row));
+        }
+    }
+
+    pub fn tetromino(&mut self, tetromino: &Tetromino) {
+        info!(target: "game_log", "{} {}", tetromino.kind, tetromino.position);
+        self.write_line(format_args!("{} {}", tetromino.kind, tetromino.position));
+    }
+
+    pub fn actions(&mut self, actions: &str) {
+        info!(target: "game_log", "{}", actions);
+        self.write_line(format_args!("{}", actions));
+    }
+
+    /// A garbage row with the hole in the given column (starting with 1).
+    pub fn garbage(&mut self, hole: usize) {
+        info!(target: "game_log", "garbage {}", hole);
+        self.write_line(format_args!("garbage {}", hole));
+    }
+
+    pub fn summary(&mut self, summary: &str) {
+        self.write_line(format_args!("\n# {}", summary));
+        self.flush();
+    }
+
+    /// A comment after the summary.
+    pub fn comment(&mut self, comment: &str) {
+        self.write_line(format_args!("# {}", comment));
+        self.flush();
+    }
+
+    fn flush(&mut self) {
+        if let Some(writer) = self.writer.as_mut() {
+            if let Err(error) = writer.flush() {
+                warn!("Flushing the game log failed: {:?}", error);
+            }
+        }
+    }
+
+    fn write_line(&mut self, line: std::fmt::Arguments<'_>) {
+        if let Some(writer) = self.writer.as_mut() {
+            if let Err(error) = writeln!(writer, "{}", line) {
+                warn!(
+                    "Writing the game log failed, so it is disabled: {:?}",
+                    error
+                );
+                self.writer = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::TetrominoKind;
+
+    use super::*;
+
+    #[test]
+    fn test_game_log() {
+        let mut game_log = GameLog::new(Vec::new());
+        game_log.size(6, 12);
+        game_log.rotation(RotationSystem::Srs);
+        game_log.board(&["|      |".into(), "|## ###|".into()]);
+        game_log.tetromino(&Tetromino {
+            kind: TetrominoKind::O,
+            position: 4,
+            rotation: 0,
+        });
+        game_log.actions("shift_left");
+        game_log.tetromino(&Tetromino {
+            kind: TetrominoKind::I,
+            position: 1,
+            rotation: 0,
+        });
+        game_log.actions("shift_right rotate shift_right");
+        game_log.garbage(7);
+        game_log.summary("Dead. Lines: 0. Tetrominoes: 2");
+        game_log.comment("Response time: total 0.001 s");
+        assert_eq!(
+            String::from_utf8(game_log.writer.unwrap()).unwrap(),
+            "size 6 12\nrotation srs\nboard 2\n|      |\n|## ###|\nO 4\nshift_left\nI 1\nshift_right rotate shift_right\ngarbage 7\n\n# Dead. Lines: 0. Tetrominoes: 2\n# Response time: total 0.001 s\n"
+        );
+    }
+}
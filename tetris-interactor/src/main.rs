@@ -1,17 +1,141 @@
-use std::io::BufRead;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use structopt::StructOpt;
+use tetris_core::{
+    builtin_player, exit_code, parse_seconds, Game, Goal, Gravity, Level, Mode, RotationSystem,
+    Sequence, SequenceEnd, StepOutcome, TetrisBoard, Tetromino, TetrominoKind, Verdict,
+    BUILTIN_PLAYERS,
+};
+use tracing::{debug, info};
+
+mod answer;
+mod feedback;
+mod game_log;
+mod replay;
+mod report;
+mod solution;
+mod verdict;
+
+use answer::Answer;
+use feedback::Feedback;
+use game_log::GameLog;
+use report::{Failure, Report};
+use solution::{ReadError, Solution, TimeLimits, DEFAULT_MAX_LINE_LENGTH};
+
+/// The default limit of the commands in a line of actions (far more than any tetromino needs).
+const DEFAULT_MAX_COMMANDS: usize = 1000;
+
+/// The number of the slowest moves that the game log lists.
+const SLOWEST_MOVES: usize = 10;
+
+/// The seed of the self-test games (the same as of the first official test).
+const SELF_TEST_ANSWER: &str = "1000 01\n";
+
+/// The goal of the self-test games on the levels with the given goal, far enough for the games to
+/// run for a while.
+fn self_test_goal(goal: Goal) -> u64 {
+    match goal {
+        Goal::Lines => 30,
+        Goal::Score => 3000,
+    }
+}
+
+/// Whether the built-in player has to reach the goal of the self-test on the level: the frol
+/// heuristic always does, and the columns only fill the board without holes with the squares.
+fn must_reach_goal(level: &Level, player_name: &str) -> bool {
+    match player_name {
+        "frol" => true,
+        "columns" => level.tetromino_kinds == [TetrominoKind::O],
+        _ => false,
+    }
+}
 
-use rand::{rngs::StdRng, seq::SliceRandom, RngCore, SeedableRng};
-use tracing::{debug, info, trace};
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// The level to play (overrides the level from the answer file)
+    #[structopt(long, env = "TETRIS_LEVEL")]
+    level: Option<u8>,
+    /// The answer file with the number of lines to clean, the seed, and the level settings
+    #[structopt(long, default_value = "answer.txt", parse(from_os_str))]
+    answer: PathBuf,
+    /// The file to write the game log to (in the tetris-viewer format)
+    #[structopt(long, env = "TETRIS_GAME_LOG", parse(from_os_str))]
+    game_log: Option<PathBuf>,
+    /// The file to write the verdict to (in the form of the testlib result, e.g. `points 90`)
+    #[structopt(long, env = "TETRIS_RESULT_FILE", parse(from_os_str))]
+    result_file: Option<PathBuf>,
+    /// The file to write the report of the game to (in JSON), including what has gone wrong
+    #[structopt(long, env = "TETRIS_REPORT", parse(from_os_str))]
+    report: Option<PathBuf>,
+    /// Play the built-in players on the built-in levels in-process to check the installation
+    #[structopt(long)]
+    self_test: bool,
+    /// The time that the solution may think about a single tetromino, in seconds
+    #[structopt(long, env = "TETRIS_MOVE_TIME_LIMIT", parse(try_from_str = parse_seconds))]
+    move_time_limit: Option<Duration>,
+    /// The total time that the solution may think during the game, in seconds
+    #[structopt(long, env = "TETRIS_GAME_TIME_LIMIT", parse(try_from_str = parse_seconds))]
+    game_time_limit: Option<Duration>,
+    /// The limit of the length of a line of actions, in bytes [default: 16384]
+    #[structopt(long, env = "TETRIS_MAX_LINE_LENGTH")]
+    max_line_length: Option<usize>,
+    /// The limit of the commands in a line of actions [default: 1000]
+    #[structopt(long, env = "TETRIS_MAX_COMMANDS")]
+    max_commands: Option<usize>,
+    /// What to send back after every locked tetromino for debugging: `none`, `landing`, or
+    /// `board` (overrides the feedback from the answer file)
+    #[structopt(long, env = "TETRIS_FEEDBACK", parse(try_from_str = parse_feedback))]
+    feedback: Option<Feedback>,
+    /// The file with the tetrominoes to give out instead of the random ones, a `kind position`
+    /// pair per line (overrides the sequence from the answer file)
+    #[structopt(long, env = "TETRIS_SEQUENCE", parse(from_os_str))]
+    sequence: Option<PathBuf>,
+    /// What happens when the sequence ends: `stop` (the game ends), `repeat`, or `random` (the
+    /// random tetrominoes follow) [default: stop]
+    #[structopt(long, env = "TETRIS_SEQUENCE_END", parse(try_from_str = parse_sequence_end))]
+    sequence_end: Option<SequenceEnd>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
 
-const EXIT_CODE_OK: i32 = 0;
-const EXIT_CODE_WA: i32 = 1;
-const EXIT_CODE_PE: i32 = 2;
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Re-simulates a game log in the tetris-viewer format and reports the result
+    Replay {
+        #[structopt(parse(from_os_str))]
+        game_log: PathBuf,
+        /// The board width (defaults to the width on the level)
+        #[structopt(long)]
+        width: Option<usize>,
+        /// The board height (defaults to the height on the level)
+        #[structopt(long)]
+        height: Option<usize>,
+        /// Allow the `hold` command
+        #[structopt(long)]
+        hold: bool,
+        /// The gravity: `drop` or `step` (defaults to the gravity on the level)
+        #[structopt(long, parse(try_from_str = parse_gravity))]
+        gravity: Option<Gravity>,
+        /// The rotation system: `classic` or `srs` (defaults to the rotation system on the level)
+        #[structopt(long, parse(try_from_str = parse_rotation_system))]
+        rotation: Option<RotationSystem>,
+    },
+}
 
+/// The adapter of the game to the protocol: it announces the tetrominoes to the solution, and
+/// applies the lines of actions that the solution sends back.
 struct Tetris {
-    rng: StdRng,
-    board: TetrisBoard,
-    score: u64,
-    score_limit: u64,
+    game: Game,
+    game_log: GameLog<Box<dyn Write>>,
+    /// The number of the lines of actions that the solution has sent.
+    moves: u64,
+    max_commands: usize,
+    feedback: Feedback,
+    /// The holes of the garbage rows that have risen since the last announcement.
+    risen_garbage: Vec<usize>,
+    failure: Option<Failure>,
 }
 
 #[derive(Debug)]
@@ -19,416 +143,826 @@ enum GameOver {
     Ok,
     WrongInput,
     Dead,
+    /// The limit of the tetrominoes is reached.
+    Survived,
+    TimeLimitExceeded,
+    /// The solution has closed its stdout before the end of the game.
+    EndOfInput,
+    /// Reading from the solution has failed (e.g. the line is not valid UTF-8).
+    InputError,
+    LineTooLong,
+    TooManyCommands,
+    /// The solution has printed something after the end of the game.
+    TrailingOutput,
+}
+
+impl From<tetris_core::GameOver> for GameOver {
+    fn from(game_over: tetris_core::GameOver) -> Self {
+        match game_over {
+            tetris_core::GameOver::Ok => Self::Ok,
+            tetris_core::GameOver::Dead => Self::Dead,
+            tetris_core::GameOver::Survived => Self::Survived,
+        }
+    }
 }
 
 impl Tetris {
-    fn new(rng: StdRng, score_limit: u64) -> Self {
+    fn new(game: Game, game_log: GameLog<Box<dyn Write>>) -> Self {
         Self {
-            rng,
-            board: TetrisBoard::default(),
-            score: 0,
-            score_limit,
+            game,
+            game_log,
+            moves: 0,
+            max_commands: DEFAULT_MAX_COMMANDS,
+            feedback: Feedback::None,
+            risen_garbage: Vec::new(),
+            failure: None,
         }
     }
 
-    fn play(&mut self) -> GameOver {
-        let mut line = String::new();
-        let stdin = std::io::stdin();
-        let mut stdin = stdin.lock();
-        while self.score < self.score_limit {
-            let mut new_tetromino = Tetromino {
-                kind: *TETROMINO_KINDS
-                    .choose(&mut self.rng)
-                    .unwrap_or(&TetrominoKind::O),
-                position: 0,
-                rotation: 0,
-            };
-            new_tetromino.position =
-                self.rng.next_u32() as usize % (11 - new_tetromino.width()) + 1;
-
-            #[cfg(not(feature = "level-9"))]
-            println!("{}", new_tetromino.position);
-            #[cfg(feature = "level-9")]
-            println!("{} {}", new_tetromino.kind, new_tetromino.position);
-            info!(target: "game_log", "{} {}", new_tetromino.kind, new_tetromino.position);
-
-            line.clear();
-            if let Err(error) = stdin.read_line(&mut line) {
-                debug!("Reading a new line from a solution failed: {:?}", error);
-                return GameOver::WrongInput;
+    /// The failure of the current move with the given reason.
+    fn failure(&self, reason: String, line: &str, tetromino: &Tetromino) -> Failure {
+        Failure {
+            reason,
+            move_number: self.moves,
+            line: Some(line.trim().to_owned()),
+            token: None,
+            tetromino: Some(tetromino.into()),
+            placement: None,
+            board: self.board_rows(),
+        }
+    }
+
+    /// The lines that the board starts with (from the top to the bottom), e.g. `|#  # |`.
+    fn starting_rows(&self) -> Vec<String> {
+        self.game
+            .level()
+            .starting_lines
+            .iter()
+            .rev()
+            .map(|line| TetrisBoard::format_line(line))
+            .collect()
+    }
+
+    fn board_rows(&self) -> Vec<String> {
+        self.game
+            .board()
+            .to_string()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    fn describe_tetromino(&self, tetromino: &Tetromino) -> String {
+        if self.game.level().reveals_tetromino_kind {
+            format!("{} {}", tetromino.kind, tetromino.position)
+        } else {
+            tetromino.position.to_string()
+        }
+    }
+
+    /// The current tetromino followed by the ones in the preview, e.g. `T 4 O 1 I 7`, the held
+    /// tetromino when the level allows to hold, e.g. `T 4 hold S 3` or `T 4 hold none`, and the
+    /// holes of the garbage rows that have risen since the previous tetromino, e.g.
+    /// `T 4 garbage 3 7`.
+    fn announcement(&self, tetromino: &Tetromino) -> String {
+        let mut announcement = std::iter::once(tetromino)
+            .chain(self.game.preview())
+            .map(|tetromino| self.describe_tetromino(tetromino))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if self.game.level().allows_hold {
+            announcement.push_str(" hold ");
+            match self.game.held_tetromino() {
+                Some(held_tetromino) => {
+                    announcement.push_str(&self.describe_tetromino(held_tetromino))
+                }
+                None => announcement.push_str("none"),
             }
-            info!(target: "game_log", "{}", line.trim());
+        }
+        if !self.risen_garbage.is_empty() {
+            announcement.push_str(" garbage");
+            for hole in &self.risen_garbage {
+                announcement.push_str(&format!(" {}", hole));
+            }
+        }
+        announcement
+    }
 
-            for action in line.split_ascii_whitespace() {
-                match action {
-                    "shift_left" => {
-                        if new_tetromino.position > 1 {
-                            new_tetromino.position -= 1;
+    fn play(&mut self, solution: &mut Solution) -> GameOver {
+        let mut line = String::new();
+        let mut actions = Vec::new();
+        let level = self.game.level();
+        if (level.board_width, level.board_height)
+            != (TetrisBoard::DEFAULT_WIDTH, TetrisBoard::DEFAULT_HEIGHT)
+        {
+            self.game_log.size(level.board_width, level.board_height);
+        }
+        if level.rotation_system != RotationSystem::Classic {
+            self.game_log.rotation(level.rotation_system);
+        }
+        let starting_rows = self.starting_rows();
+        if !starting_rows.is_empty() {
+            send(&format!("board {}", starting_rows.len()));
+            for row in &starting_rows {
+                send(row);
+            }
+            self.game_log.board(&starting_rows);
+        }
+        while !self.game.is_over() {
+            // A hold ends the line of actions, and the tetromino that replaces the held one is
+            // announced as a new one
+            let mut tetromino = self.game.spawn_next();
+            loop {
+                send(&self.announcement(&tetromino));
+                self.game_log.tetromino(&tetromino);
+                self.risen_garbage.clear();
+
+                line.clear();
+                self.moves += 1;
+                if let Err(error) = solution.read_line(&mut line) {
+                    debug!("Reading a new line from a solution failed: {}", error);
+                    self.game_log.actions("");
+                    self.failure = Some(Failure {
+                        line: None,
+                        ..self.failure(error.to_string(), "", &tetromino)
+                    });
+                    return match error {
+                        ReadError::EndOfInput => GameOver::EndOfInput,
+                        ReadError::Io(_) => GameOver::InputError,
+                        ReadError::LineTooLong(_) => GameOver::LineTooLong,
+                        ReadError::MoveTimeLimitExceeded | ReadError::GameTimeLimitExceeded => {
+                            GameOver::TimeLimitExceeded
                         }
-                    }
-                    "shift_right" => {
-                        if new_tetromino.position + new_tetromino.width() <= self.board.width() {
-                            new_tetromino.position += 1;
+                    };
+                }
+                self.game_log.actions(line.trim());
+
+                if let Some(action) = line.split_ascii_whitespace().nth(self.max_commands) {
+                    debug!("More than {} commands", self.max_commands);
+                    self.failure = Some(Failure {
+                        token: Some(action.to_owned()),
+                        ..self.failure(
+                            format!("the line has more than {} commands", self.max_commands),
+                            &line,
+                            &tetromino,
+                        )
+                    });
+                    return GameOver::TooManyCommands;
+                }
+
+                actions.clear();
+                for action in line.split_ascii_whitespace() {
+                    match action.parse() {
+                        Ok(action) => actions.push(action),
+                        Err(()) => {
+                            debug!("Unknown command {:?}", action);
+                            self.failure = Some(Failure {
+                                token: Some(action.to_owned()),
+                                ..self.failure(
+                                    format!("unknown command {:?}", action),
+                                    &line,
+                                    &tetromino,
+                                )
+                            });
+                            return GameOver::WrongInput;
                         }
                     }
-                    "rotate" => {
-                        new_tetromino.rotation += 90;
-                        // Allow rotating tetromino when it does not fit by just updating the position
-                        let rightmost_allowed_position =
-                            self.board.width() - new_tetromino.width() + 1;
-                        if new_tetromino.position > rightmost_allowed_position {
-                            new_tetromino.position = rightmost_allowed_position;
+                }
+                match self.game.apply_actions(&actions) {
+                    Ok(StepOutcome::Held(replacing_tetromino)) => tetromino = replacing_tetromino,
+                    Ok(StepOutcome::Locked {
+                        tetromino: locked_tetromino,
+                        top_line,
+                        cleared_lines,
+                        ..
+                    }) => {
+                        for line in self.feedback.lines(
+                            &locked_tetromino,
+                            top_line,
+                            cleared_lines,
+                            self.game.board(),
+                        ) {
+                            send(&line);
                         }
+                        for &hole in self.game.risen_garbage() {
+                            self.game_log.garbage(hole);
+                        }
+                        self.risen_garbage = self.game.risen_garbage().to_vec();
+                        break;
                     }
-                    "" => {}
-                    _ => return GameOver::WrongInput,
-                };
-            }
-            debug_assert!(new_tetromino.position >= 1);
-            debug_assert!(new_tetromino.position <= self.board.width() - new_tetromino.width() + 1);
-            if let Err(_) = self.board.try_apply_tetromino(new_tetromino) {
-                return GameOver::Dead;
+                    Ok(StepOutcome::ToppedOut(placement)) => {
+                        // The board has not changed, since the tetromino has not fit into it
+                        self.failure = Some(Failure {
+                            placement: Some((&placement).into()),
+                            ..self.failure(
+                                "the tetromino has not fit into the board".into(),
+                                &line,
+                                &tetromino,
+                            )
+                        });
+                        break;
+                    }
+                    Err(error) => {
+                        debug!("Illegal actions: {}", error);
+                        self.failure = Some(Failure {
+                            token: line
+                                .split_ascii_whitespace()
+                                .nth(error.index())
+                                .map(String::from),
+                            ..self.failure(error.to_string(), &line, &tetromino)
+                        });
+                        return GameOver::WrongInput;
+                    }
+                }
             }
-
-            self.score += self.board.clean_full_lines();
         }
+        self.game
+            .game_over()
+            .expect("the loop only ends when the game is over")
+            .into()
+    }
 
-        GameOver::Ok
+    /// Signals the game over to the solution, and makes sure that the solution has nothing else
+    /// to send. A blank line is fine, since a solution may take the `0` for a tetromino.
+    fn finish(&mut self, solution: &mut Solution) -> Option<GameOver> {
+        send("0");
+        let mut line = String::new();
+        let reason = match solution.read_line(&mut line) {
+            Ok(()) if line.trim().is_empty() => return None,
+            // The solution may take its time to exit
+            Err(ReadError::EndOfInput)
+            | Err(ReadError::MoveTimeLimitExceeded)
+            | Err(ReadError::GameTimeLimitExceeded) => return None,
+            Ok(()) => "the solution has sent a line after the end of the game".to_owned(),
+            Err(error) => format!(
+                "the solution has sent a line after the end of the game: {}",
+                error
+            ),
+        };
+        debug!("Trailing output: {:?}", line);
+        self.failure = Some(Failure {
+            reason,
+            move_number: self.moves + 1,
+            line: Some(line.trim().to_owned()).filter(|line| !line.is_empty()),
+            token: line.split_ascii_whitespace().next().map(String::from),
+            tetromino: None,
+            placement: None,
+            board: self.board_rows(),
+        });
+        Some(GameOver::TrailingOutput)
     }
 }
 
-#[derive(Debug, Clone, Copy, derive_more::Display)]
-enum TetrominoKind {
-    #[cfg(feature = "level-9")]
-    I,
-    O,
-    #[cfg(feature = "level-9")]
-    T,
-    #[cfg(feature = "level-9")]
-    S,
-    #[cfg(feature = "level-9")]
-    Z,
-    #[cfg(feature = "level-9")]
-    J,
-    #[cfg(feature = "level-9")]
-    L,
+/// Sends a line to the solution. The solution that has exited cannot be written to, but that is
+/// not an error of the interactor, and the solution will not respond anyway.
+fn send(line: &str) {
+    let stdout = std::io::stdout();
+    if let Err(error) = writeln!(stdout.lock(), "{}", line) {
+        debug!("Writing to the solution failed: {}", error);
+    }
 }
 
-#[cfg(not(feature = "level-9"))]
-const TETROMINO_KINDS: &[TetrominoKind] = &[TetrominoKind::O];
+fn main() {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing_subscriber::filter::LevelFilter::INFO)
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let opts = Opts::from_args();
+
+    let exit_code = match opts.command {
+        Some(Command::Replay {
+            ref game_log,
+            width,
+            height,
+            hold,
+            gravity,
+            rotation,
+        }) => replay_game_log(game_log, {
+            let mut level = load_level(opts.level.unwrap_or_default());
+            level.board_width = width.unwrap_or(level.board_width);
+            level.board_height = height.unwrap_or(level.board_height);
+            level.allows_hold |= hold;
+            level.gravity = gravity.unwrap_or(level.gravity);
+            level.rotation_system = rotation.unwrap_or(level.rotation_system);
+            level
+        }),
+        None if opts.self_test => self_test(),
+        None => play_game(opts),
+    };
+    std::process::exit(exit_code);
+}
 
-#[cfg(feature = "level-9")]
-const TETROMINO_KINDS: &[TetrominoKind] = &[
-    TetrominoKind::I,
-    TetrominoKind::O,
-    TetrominoKind::T,
-    TetrominoKind::S,
-    TetrominoKind::Z,
-    TetrominoKind::J,
-    TetrominoKind::L,
-];
+fn parse_gravity(gravity: &str) -> Result<Gravity, String> {
+    gravity
+        .parse()
+        .map_err(|()| format!("unknown gravity {:?}", gravity))
+}
 
-#[derive(Debug)]
-struct Tetromino {
-    kind: TetrominoKind,
-    position: usize,
-    rotation: usize,
+fn parse_feedback(feedback: &str) -> Result<Feedback, String> {
+    feedback
+        .parse()
+        .map_err(|()| format!("unknown feedback {:?}", feedback))
 }
 
-impl Tetromino {
-    pub fn blocks(&self) -> &'static [(usize, usize)] {
-        match self.kind {
-            #[cfg(feature = "level-9")]
-            TetrominoKind::I => match self.rotation % 180 {
-                0 => &[(0, 0), (0, 1), (0, 2), (0, 3)],
-                _ => &[(0, 0), (1, 0), (2, 0), (3, 0)],
-            },
-            TetrominoKind::O => &[(0, 0), (1, 0), (0, 1), (1, 1)],
-            #[cfg(feature = "level-9")]
-            TetrominoKind::T => match self.rotation % 360 {
-                0 => &[(0, 0), (1, 0), (2, 0), (1, 1)],
-                90 => &[(1, 0), (1, 1), (1, 2), (0, 1)],
-                180 => &[(0, 1), (1, 1), (2, 1), (1, 0)],
-                _ => &[(0, 0), (0, 1), (0, 2), (1, 1)],
-            },
-            #[cfg(feature = "level-9")]
-            TetrominoKind::S => match self.rotation % 180 {
-                0 => &[(0, 1), (1, 1), (1, 0), (2, 0)],
-                _ => &[(0, 0), (0, 1), (1, 1), (1, 2)],
-            },
-            #[cfg(feature = "level-9")]
-            TetrominoKind::Z => match self.rotation % 180 {
-                0 => &[(0, 0), (1, 0), (1, 1), (2, 1)],
-                _ => &[(1, 0), (1, 1), (0, 1), (0, 2)],
-            },
-            #[cfg(feature = "level-9")]
-            TetrominoKind::J => match self.rotation % 360 {
-                0 => &[(1, 0), (1, 1), (1, 2), (0, 2)],
-                90 => &[(0, 0), (0, 1), (1, 1), (2, 1)],
-                180 => &[(0, 0), (0, 1), (0, 2), (1, 0)],
-                _ => &[(0, 0), (1, 0), (2, 0), (2, 1)],
-            },
-            #[cfg(feature = "level-9")]
-            TetrominoKind::L => match self.rotation % 360 {
-                0 => &[(0, 0), (0, 1), (0, 2), (1, 2)],
-                90 => &[(0, 0), (1, 0), (2, 0), (0, 1)],
-                180 => &[(0, 0), (1, 0), (1, 1), (1, 2)],
-                _ => &[(0, 1), (1, 1), (2, 1), (2, 0)],
-            },
-        }
-    }
+fn parse_sequence_end(end: &str) -> Result<SequenceEnd, String> {
+    end.parse()
+        .map_err(|()| format!("unknown end of the sequence {:?}", end))
+}
 
-    fn width(&self) -> usize {
-        self.blocks().iter().map(|(x, _)| x).max().unwrap_or(&0) + 1
-    }
+fn parse_rotation_system(rotation_system: &str) -> Result<RotationSystem, String> {
+    rotation_system
+        .parse()
+        .map_err(|()| format!("unknown rotation system {:?}", rotation_system))
 }
 
-#[derive(Debug, Clone, Copy)]
-enum TetrisCell {
-    Empty,
-    Occupied,
+fn load_level(number: u8) -> Level {
+    Level::builtin(number).unwrap_or_else(|| panic!("Level {} is not defined", number))
 }
 
-impl Default for TetrisCell {
-    fn default() -> Self {
-        Self::Empty
+fn replay_game_log(game_log: &Path, level: Level) -> i32 {
+    let game_log = std::fs::read_to_string(game_log).unwrap_or_else(|error| {
+        panic!("The game log file {:?} cannot be read: {}", game_log, error)
+    });
+    let report = replay::replay(&level, &game_log);
+    print!("{}", report);
+    match report.outcome {
+        replay::ReplayOutcome::InvalidLine { .. } => exit_code::PE,
+        _ => exit_code::OK,
     }
 }
 
-type TetrisBoardLine = [TetrisCell; 10];
-
-#[derive(Default)]
-struct TetrisBoard([TetrisBoardLine; 20]);
+/// The summary line of the game log.
+fn summary(game_status: &dyn std::fmt::Debug, game: &Game) -> String {
+    format!(
+        "{:?}. Lines: {}. Score: {}. Tetrominoes: {}",
+        game_status,
+        game.lines(),
+        game.scoring().points,
+        game.locked_tetrominoes()
+    )
+}
 
-impl std::fmt::Display for TetrisBoard {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for line in self.0.iter().rev() {
-            format!(
-                "|{}|\n",
-                line.iter()
-                    .map(|cell| match cell {
-                        TetrisCell::Empty => ' ',
-                        TetrisCell::Occupied => '#',
-                    })
-                    .collect::<String>()
-            )
-            .fmt(f)?;
+fn self_test() -> i32 {
+    let answer: Answer = SELF_TEST_ANSWER
+        .parse()
+        .expect("the self-test answer is valid");
+    let mut exit_code = exit_code::OK;
+    for &level_number in Level::BUILTIN_NUMBERS {
+        for &player_name in BUILTIN_PLAYERS {
+            let level = load_level(level_number);
+            let must_reach_goal = must_reach_goal(&level, player_name);
+            let goal = self_test_goal(level.goal);
+            let mut game = Game::new(level, answer.seed, goal);
+            let mut player =
+                builtin_player(player_name, answer.seed).expect("the built-in player exists");
+            // The built-in players never send illegal actions
+            let (status, summary) = match game.play(player.as_mut()).map(GameOver::from) {
+                Ok(GameOver::Ok) => ("ok", summary(&GameOver::Ok, &game)),
+                Ok(game_over) if !must_reach_goal => ("ok", summary(&game_over, &game)),
+                Ok(game_over) => ("FAILED", summary(&game_over, &game)),
+                Err(error) => ("FAILED", format!("Illegal actions: {}", error)),
+            };
+            if status != "ok" {
+                exit_code = exit_code::WA;
+            }
+            println!(
+                "Level {}, {}: {}. {}",
+                level_number, player_name, status, summary
+            );
         }
-        "_".repeat(self.width() + 2).fmt(f)
     }
+    exit_code
 }
 
-impl TetrisBoard {
-    pub fn width(&self) -> usize {
-        self.0[0].len()
-    }
+fn play_game(opts: Opts) -> i32 {
+    info!("Initializing Tetris interactor");
 
-    pub fn fit_tetromino(
-        board: &mut [TetrisBoardLine],
-        tetromino: &Tetromino,
-        should_save: bool,
-    ) -> Result<(), ()> {
-        if board.is_empty() {
-            return Err(());
-        }
-        let board_top = board.len() - 1;
-        let positions: Vec<(usize, usize)> = tetromino
-            .blocks()
-            .into_iter()
-            .map(|(x, y)| (x + tetromino.position - 1, *y))
-            .collect();
-
-        for position in &positions {
-            if position.1 >= board.len() {
-                return Err(());
-            }
-            if let TetrisCell::Occupied = board[board_top - position.1][position.0] {
-                return Err(());
-            }
-        }
-        if should_save {
-            for position in &positions {
-                board[board_top - position.1][position.0] = TetrisCell::Occupied;
-            }
-        }
-        Ok(())
+    let mut answer: Answer = std::fs::read_to_string(&opts.answer)
+        .unwrap_or_default()
+        .parse()
+        .unwrap_or_else(|error| panic!("The answer file is invalid: {}", error));
+    if let Some(path) = &opts.sequence {
+        let sequence = std::fs::read_to_string(path).unwrap_or_else(|error| {
+            panic!("The sequence file {:?} cannot be read: {}", path, error)
+        });
+        answer.sequence = Some(
+            Sequence::parse(sequence.lines(), SequenceEnd::default()).unwrap_or_else(|error| {
+                panic!("The sequence file {:?} is invalid: {}", path, error)
+            }),
+        );
+    }
+    if let (Some(sequence), Some(end)) = (answer.sequence.as_mut(), opts.sequence_end) {
+        sequence.end = end;
+    }
+    let mut level = load_level(opts.level.or(answer.level).unwrap_or_default());
+    answer
+        .configure_level(&mut level)
+        .unwrap_or_else(|error| panic!("The answer file is invalid: {}", error));
+    info!(
+        "Playing level {} on the {}x{} board with the {} randomizer, the preview of {}, the {} \
+         gravity, and the {} rotation to reach the {} goal in the {} mode with the limit of {:?} \
+         tetrominoes",
+        level.number,
+        level.board_width,
+        level.board_height,
+        level.randomizer,
+        level.preview,
+        level.gravity,
+        level.rotation_system,
+        level.goal,
+        level.mode,
+        level.piece_limit
+    );
+    if let Some(sequence) = &level.sequence {
+        info!(
+            "Giving out the sequence of {} tetrominoes ({} at the end)",
+            sequence.len(),
+            sequence.end
+        );
     }
+    let game_log = match opts.game_log {
+        Some(path) => GameLog::new(Box::new(std::io::BufWriter::new(
+            std::fs::File::create(&path).unwrap_or_else(|error| {
+                panic!("The game log file {:?} cannot be created: {}", path, error)
+            }),
+        )) as Box<dyn Write>),
+        None => GameLog::disabled(),
+    };
+    let mut tetris = Tetris::new(Game::new(level, answer.seed, answer.score_limit), game_log);
 
-    pub fn try_apply_tetromino(&mut self, tetromino: Tetromino) -> Result<(), ()> {
-        trace!("Trying to apply {:?} to the board\n{}", tetromino, self);
-        let mut vertical_position = self.0.len();
-        while Self::fit_tetromino(&mut self.0[..vertical_position], &tetromino, false).is_ok() {
-            vertical_position -= 1;
-        }
-        if vertical_position == self.0.len() {
-            debug!("Tetromino could not get placed on the board");
-            Err(())
-        } else {
-            debug!(
-                "Tetromino {} shifted by {} will get placed on the line {}",
-                tetromino.kind, tetromino.position, vertical_position
-            );
-            Self::fit_tetromino(&mut self.0[..vertical_position + 1], &tetromino, true)
-                .expect("unreachable");
-            trace!("Applied {:?} to the board\n{}", tetromino, self);
+    tetris.max_commands = opts.max_commands.unwrap_or(DEFAULT_MAX_COMMANDS);
+    tetris.feedback = opts.feedback.or(answer.feedback).unwrap_or_default();
 
-            Ok(())
+    let mut solution = Solution::from_stdin(
+        TimeLimits {
+            per_move: opts.move_time_limit,
+            per_game: opts.game_time_limit,
+        },
+        opts.max_line_length.unwrap_or(DEFAULT_MAX_LINE_LENGTH),
+    );
+    let mut game_status = tetris.play(&mut solution);
+    let timing_report = solution.timing_report(SLOWEST_MOVES);
+    // Once the game is over, the solution must not send anything, but the solution that has
+    // broken the protocol is not waited for, since it is likely not listening anymore
+    match game_status {
+        GameOver::Ok | GameOver::Dead | GameOver::Survived => {
+            game_status = tetris.finish(&mut solution).unwrap_or(game_status)
         }
+        GameOver::EndOfInput => {}
+        _ => send("0"),
+    }
+    let game = &tetris.game;
+    info!(
+        "{:?}. Lines: {}. Score: {}",
+        game_status,
+        game.lines(),
+        game.scoring().points
+    );
+    let summary = summary(&game_status, game);
+    tetris.game_log.summary(&summary);
+    for timing in timing_report {
+        info!("{}", timing);
+        tetris.game_log.comment(&timing);
     }
 
-    fn clean_full_lines(&mut self) -> u64 {
-        let mut cleaned_lines = 0;
-        let mut line_index = 0;
-        while line_index < self.0.len() {
-            if self.0[line_index].iter().all(|cell| match cell {
-                TetrisCell::Occupied => true,
-                _ => false,
-            }) {
-                self.0.copy_within(line_index + 1.., line_index);
-                cleaned_lines += 1;
-            } else {
-                line_index += 1;
+    // The game that has survived in the ultra mode is won when it has reached the goal, and the
+    // solution that has stopped playing is graded by what it has reached
+    let verdict = match game_status {
+        GameOver::WrongInput
+        | GameOver::InputError
+        | GameOver::LineTooLong
+        | GameOver::TooManyCommands
+        | GameOver::TrailingOutput => Verdict::PresentationError,
+        GameOver::TimeLimitExceeded => Verdict::TimeLimitExceeded,
+        // The sprint is graded by how many tetrominoes it has taken to reach the goal
+        _ if tetris.game.is_goal_reached() && tetris.game.level().mode == Mode::Sprint => {
+            match verdict::sprint_percent(
+                tetris.game.sprint_par(),
+                tetris.game.locked_tetrominoes(),
+            ) {
+                100 => Verdict::Ok,
+                percent => Verdict::Partial(percent),
             }
         }
-
-        debug!("Cleaned {} lines.", cleaned_lines);
-        trace!("The board is:\n{}", self);
-        cleaned_lines
+        _ if tetris.game.is_goal_reached() => Verdict::Ok,
+        GameOver::Ok | GameOver::Dead | GameOver::Survived | GameOver::EndOfInput => match answer
+            .grading
+            .percent(tetris.game.progress(), tetris.game.score_limit())
+        {
+            0 => Verdict::WrongAnswer,
+            percent => Verdict::Partial(percent),
+        },
+    };
+    info!("Verdict: {}", verdict);
+    if let Some(failure) = &tetris.failure {
+        eprint!("{}", failure);
+    }
+    if let Some(path) = opts.report {
+        let game = &tetris.game;
+        let report = Report {
+            verdict: verdict.to_string(),
+            exit_code: verdict.exit_code(),
+            game_over: format!("{:?}", game_status),
+            lines: game.lines(),
+            score: game.scoring().points,
+            tetrominoes: game.locked_tetrominoes(),
+            failure: tetris.failure,
+        };
+        let report = serde_json::to_string_pretty(&report).expect("the report is serializable");
+        std::fs::write(&path, report + "\n").unwrap_or_else(|error| {
+            panic!("The report file {:?} cannot be written: {}", path, error)
+        });
     }
+    if let Some(path) = opts.result_file {
+        std::fs::write(&path, format!("{} {}\n", verdict, summary)).unwrap_or_else(|error| {
+            panic!("The result file {:?} cannot be written: {}", path, error)
+        });
+    }
+    verdict.exit_code()
 }
 
-fn main() {
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .with_max_level(tracing_subscriber::filter::LevelFilter::INFO)
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
-
-    info!("Initializing Tetris interactor");
-
-    let mut seed = [0; 32];
-    let answer = std::fs::read_to_string("answer.txt").unwrap_or(String::new());
-    let seed_len = seed.len().min(answer.len());
-    seed[..seed_len].copy_from_slice(answer.as_bytes());
-    let mut tetris = Tetris::new(
-        rand::rngs::StdRng::from_seed(seed),
-        answer
-            .split_ascii_whitespace()
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap(),
-    );
-
-    let game_status = tetris.play();
-    info!("{:?}. Score: {}", game_status, tetris.score);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetris_core::{Action, TetrominoGenerator};
 
-    // Signal game over
-    println!("0");
+    #[test]
+    fn test_levels() {
+        for &level_number in Level::BUILTIN_NUMBERS {
+            let level = Level::builtin(level_number).unwrap();
+            check_level(level.clone());
+            check_level(Level {
+                board_width: 4,
+                ..level.clone()
+            });
+            check_level(Level {
+                board_width: 6,
+                board_height: 40,
+                ..level
+            });
+        }
+    }
 
-    // Wait for the solution to react to catch WA
-    let mut line = String::new();
-    std::io::stdin().read_line(&mut line).ok();
+    fn check_level(level: Level) {
+        let tetris = Tetris::new(Game::new(level.clone(), [0; 32], 10), GameLog::disabled());
+        assert_eq!(tetris.game.board().width(), level.board_width);
+        assert_eq!(tetris.game.board().height(), level.board_height);
+        let mut generator = TetrominoGenerator::new(&level, [0; 32]);
+        for _ in 0..100 {
+            let tetromino = generator.next_tetromino();
+            assert!(level.tetromino_kinds.contains(&tetromino.kind));
+            assert!(tetromino.position >= 1);
+            assert!(tetromino.position + tetromino.width() - 1 <= level.board_width);
+
+            let announcement = tetris.announcement(&tetromino);
+            let mut tokens = announcement.split_ascii_whitespace();
+            if level.reveals_tetromino_kind {
+                assert_eq!(tokens.next(), Some(tetromino.kind.to_string().as_str()));
+            }
+            assert_eq!(tokens.next(), Some(tetromino.position.to_string().as_str()));
+            assert_eq!(tokens.next(), None);
+        }
+    }
 
-    let exit_code = match game_status {
-        GameOver::Ok => EXIT_CODE_OK,
-        GameOver::WrongInput => EXIT_CODE_PE,
-        GameOver::Dead => EXIT_CODE_WA,
-    };
-    std::process::exit(exit_code);
-}
+    #[test]
+    fn test_preview() {
+        // The board is high enough for the tetrominoes to pile up without clearing lines
+        let level = Level {
+            preview: 3,
+            board_height: 400,
+            ..Level::builtin(9).unwrap()
+        };
+        let mut generator = TetrominoGenerator::new(&level, [0; 32]);
+        let tetrominoes = (0..100)
+            .map(|_| generator.next_tetromino())
+            .collect::<Vec<_>>();
+        let mut tetris = Tetris::new(Game::new(level, [0; 32], 10), GameLog::disabled());
+        for (index, tetromino) in tetrominoes.iter().take(97).enumerate() {
+            assert!(tetris.game.spawn_next() == *tetromino);
+            let expected_announcement = tetrominoes[index..index + 4]
+                .iter()
+                .map(|tetromino| format!("{} {}", tetromino.kind, tetromino.position))
+                .collect::<Vec<_>>()
+                .join(" ");
+            assert_eq!(tetris.announcement(tetromino), expected_announcement);
+            tetris.game.apply_actions(&[]).unwrap();
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    #![allow(non_snake_case)]
+    #[test]
+    fn test_hold() {
+        let level = Level {
+            allows_hold: true,
+            ..Level::builtin(9).unwrap()
+        };
+        let mut tetris = Tetris::new(Game::new(level, [0; 32], 10), GameLog::disabled());
+        let first = tetris.game.spawn_next();
+        assert_eq!(
+            tetris.announcement(&first),
+            format!("{} {} hold none", first.kind, first.position)
+        );
+
+        let second = match tetris.game.apply_actions(&[Action::Hold]) {
+            Ok(StepOutcome::Held(second)) => second,
+            outcome => panic!("{:?}", outcome),
+        };
+        assert_eq!(
+            tetris.announcement(&second),
+            format!(
+                "{} {} hold {} {}",
+                second.kind, second.position, first.kind, first.position
+            )
+        );
+    }
 
-    use insta::assert_snapshot;
+    /// The first tetrominoes of the official tests, which must stay the same while
+    /// `TetrominoGenerator::VERSION` is the same.
+    const GOLDEN_SEQUENCES: &[(&str, &str)] = &[
+        (
+            include_str!("../../problem-level-0/tests/01.out"),
+            "O 7, O 7, O 9, O 3, O 4, O 6, O 1, O 6, O 6, O 6, O 6, O 1, O 6, O 6, O 5, O 6",
+        ),
+        (
+            include_str!("../../problem-level-0/tests/02.out"),
+            "O 5, O 1, O 2, O 9, O 3, O 6, O 2, O 6, O 6, O 9, O 5, O 2, O 4, O 1, O 2, O 9",
+        ),
+        (
+            include_str!("../../problem-level-0/tests/03.out"),
+            "O 2, O 5, O 8, O 6, O 9, O 2, O 7, O 8, O 9, O 4, O 3, O 3, O 8, O 4, O 6, O 5",
+        ),
+        (
+            include_str!("../../problem-level-0/tests/04.out"),
+            "O 1, O 1, O 3, O 6, O 1, O 9, O 5, O 4, O 2, O 2, O 1, O 2, O 4, O 7, O 7, O 2",
+        ),
+        (
+            include_str!("../../problem-level-0/tests/05.out"),
+            "O 6, O 1, O 4, O 7, O 2, O 1, O 4, O 5, O 8, O 5, O 6, O 5, O 1, O 3, O 6, O 8",
+        ),
+        (
+            include_str!("../../problem-level-0/tests/06.out"),
+            "O 8, O 4, O 2, O 4, O 1, O 6, O 1, O 1, O 4, O 2, O 2, O 4, O 5, O 1, O 9, O 7",
+        ),
+        (
+            include_str!("../../problem-level-0/tests/07.out"),
+            "O 6, O 2, O 1, O 8, O 8, O 6, O 8, O 7, O 8, O 1, O 7, O 5, O 7, O 1, O 3, O 2",
+        ),
+        (
+            include_str!("../../problem-level-0/tests/08.out"),
+            "O 9, O 7, O 7, O 5, O 8, O 9, O 6, O 9, O 4, O 2, O 5, O 1, O 5, O 8, O 1, O 7",
+        ),
+        (
+            include_str!("../../problem-level-0/tests/09.out"),
+            "O 8, O 5, O 2, O 4, O 1, O 8, O 8, O 9, O 9, O 4, O 4, O 5, O 1, O 1, O 6, O 9",
+        ),
+        (
+            include_str!("../../problem-level-0/tests/10.out"),
+            "O 9, O 9, O 3, O 9, O 4, O 5, O 8, O 4, O 9, O 3, O 9, O 6, O 7, O 1, O 7, O 5",
+        ),
+        (
+            include_str!("../../problem-level-1/tests/01.out"),
+            "I 7, I 2, I 4, O 4, I 7, O 6, O 2, O 3, I 3, O 5, I 9, O 9, I 8, I 3, O 5, I 10",
+        ),
+        (
+            include_str!("../../problem-level-2/tests/01.out"),
+            "I 2, O 9, I 2, O 6, I 9, I 9, O 5, O 6, O 5, I 6, I 5, I 4, O 6, O 7, O 2, I 7",
+        ),
+        (
+            include_str!("../../problem-level-3/tests/01.out"),
+            "O 1, I 9, O 7, I 4, T 5, I 2, T 1, I 3, I 2, O 1, O 4, O 2, T 7, I 4, T 2, T 3",
+        ),
+        (
+            include_str!("../../problem-level-4/tests/01.out"),
+            "O 4, T 1, O 1, T 6, T 6, T 3, T 6, O 9, O 2, I 5, O 3, I 4, I 3, O 5, I 6, I 2",
+        ),
+        (
+            include_str!("../../problem-level-5/tests/01.out"),
+            "I 1, L 6, I 8, L 7, I 6, O 1, L 5, O 4, I 7, T 6, I 9, O 7, O 8, I 7, J 7, I 4",
+        ),
+        (
+            include_str!("../../problem-level-6/tests/01.out"),
+            "L 7, O 2, L 9, O 7, J 4, L 7, T 6, I 10, O 2, I 1, T 3, I 6, J 3, I 9, I 3, L 6",
+        ),
+        (
+            include_str!("../../problem-level-7/tests/01.out"),
+            "L 7, Z 2, L 2, J 4, I 4, I 1, O 5, I 2, L 4, O 5, O 4, Z 5, O 5, S 7, O 8, S 1",
+        ),
+        (
+            include_str!("../../problem-level-8/tests/01.out"),
+            "J 5, T 2, S 6, J 7, L 5, T 7, L 6, S 7, S 5, J 6, I 7, S 8, O 6, S 3, L 2, O 1",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/01.out"),
+            "O 3, I 3, Z 4, O 9, J 8, O 3, S 5, Z 8, J 9, Z 3, J 8, J 1, S 4, T 3, T 2, J 6",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/02.out"),
+            "T 5, Z 2, I 9, Z 8, Z 4, S 8, S 4, L 9, L 7, O 2, Z 6, I 3, I 6, T 3, O 4, I 10",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/03.out"),
+            "T 1, S 2, T 5, O 8, S 7, L 8, I 2, S 6, Z 5, I 10, J 2, S 3, T 4, L 5, O 5, I 6",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/04.out"),
+            "T 4, L 8, I 1, S 5, T 3, L 6, O 3, I 4, S 5, J 4, L 2, S 3, S 2, T 2, L 8, Z 6",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/05.out"),
+            "J 6, J 6, J 2, T 5, L 5, I 7, O 3, S 7, T 2, J 7, I 10, T 6, L 7, J 1, S 3, I 6",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/06.out"),
+            "S 6, S 7, S 5, L 9, J 7, T 6, O 9, Z 1, L 7, T 1, L 2, O 2, I 9, O 9, Z 6, T 1",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/07.out"),
+            "Z 6, O 1, Z 8, J 2, I 7, O 4, S 8, I 6, J 2, Z 3, J 7, O 6, Z 5, L 6, S 7, L 3",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/08.out"),
+            "L 1, I 10, L 2, S 4, S 8, S 4, S 7, S 4, J 7, L 9, O 4, S 5, I 2, I 7, O 2, T 7",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/09.out"),
+            "Z 3, J 7, L 8, S 8, Z 2, T 1, T 1, O 5, L 1, O 7, I 10, I 6, T 8, S 1, O 5, O 6",
+        ),
+        (
+            include_str!("../../problem-level-9/tests/10.out"),
+            "J 8, I 7, L 7, O 9, J 6, Z 7, S 2, S 7, T 2, O 1, S 5, T 5, Z 4, O 1, O 4, Z 7",
+        ),
+    ];
 
-    use super::*;
+    #[test]
+    fn test_self_test() {
+        assert_eq!(self_test(), exit_code::OK);
+    }
 
     #[test]
-    fn test_tetromino_blocks() {
-        for tetromino_kind in TETROMINO_KINDS {
-            for &rotation in &[0, 90, 180, 270] {
-                let mut board = TetrisBoard::default();
-                board
-                    .try_apply_tetromino(Tetromino {
-                        kind: *tetromino_kind,
-                        position: 1,
-                        rotation,
-                    })
-                    .unwrap();
-                assert_snapshot!(
-                    format!("tetromino_blocks_{}_{}", tetromino_kind, rotation),
-                    board.to_string()
-                );
-            }
+    fn test_golden_sequences() {
+        assert_eq!(TetrominoGenerator::VERSION, 1);
+        for (answer, golden_sequence) in GOLDEN_SEQUENCES {
+            let answer: Answer = answer.parse().unwrap();
+            let level = Level::builtin(answer.level.unwrap_or_default()).unwrap();
+            let mut generator = TetrominoGenerator::new(&level, answer.seed);
+            let sequence = (0..16)
+                .map(|_| {
+                    let tetromino = generator.next_tetromino();
+                    format!("{} {}", tetromino.kind, tetromino.position)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            assert_eq!(&sequence, golden_sequence);
         }
     }
 
-    #[cfg(feature = "level-9")]
+    /// The statements of the levels with the number of the lines that their samples clear.
+    const STATEMENTS: &[(u8, &str, u64)] = &[
+        (0, include_str!("../../problem-level-0.tex"), 2),
+        (1, include_str!("../../problem-level-1.tex"), 2),
+        (2, include_str!("../../problem-level-2.tex"), 2),
+        (3, include_str!("../../problem-level-3.tex"), 1),
+        (4, include_str!("../../problem-level-4.tex"), 1),
+        (5, include_str!("../../problem-level-5.tex"), 1),
+        (6, include_str!("../../problem-level-6.tex"), 1),
+        (7, include_str!("../../problem-level-7.tex"), 1),
+        (8, include_str!("../../problem-level-8.tex"), 1),
+        (9, include_str!("../../problem-level-9.tex"), 2),
+    ];
+
     #[test]
-    fn test_tetris_board() {
-        let mut board = TetrisBoard::default();
-
-        board
-            .try_apply_tetromino(Tetromino {
-                kind: TetrominoKind::I,
-                position: 1,
-                rotation: 90,
-            })
-            .unwrap();
-        board.clean_full_lines();
-        assert_snapshot!(board.to_string());
-
-        board
-            .try_apply_tetromino(Tetromino {
-                kind: TetrominoKind::I,
-                position: 1,
-                rotation: 270,
-            })
-            .unwrap();
-        board.clean_full_lines();
-        assert_snapshot!(board.to_string());
-
-        board
-            .try_apply_tetromino(Tetromino {
-                kind: TetrominoKind::I,
-                position: 9,
-                rotation: 180,
-            })
-            .unwrap();
-        board.clean_full_lines();
-        assert_snapshot!(board.to_string());
-
-        board
-            .try_apply_tetromino(Tetromino {
-                kind: TetrominoKind::I,
-                position: 10,
-                rotation: 360,
-            })
-            .unwrap();
-        board.clean_full_lines();
-        assert_snapshot!(board.to_string());
-        board
-            .try_apply_tetromino(Tetromino {
-                kind: TetrominoKind::O,
-                position: 5,
-                rotation: 90,
-            })
-            .unwrap();
-        board.clean_full_lines();
-        assert_snapshot!(board.to_string());
-
-        board
-            .try_apply_tetromino(Tetromino {
-                kind: TetrominoKind::O,
-                position: 7,
-                rotation: 90,
-            })
-            .unwrap();
-        assert_snapshot!(board.to_string());
-        board.clean_full_lines();
-        assert_snapshot!(board.to_string());
+    fn test_statement_samples() {
+        for &(level_number, statement, sample_lines) in STATEMENTS {
+            let level = Level::builtin(level_number).unwrap();
+            // The sample is `\exmp{<input>}{<output>}%` with the lines separated by empty lines
+            let sample = statement.split("\\exmp{").nth(1).unwrap();
+            let (input, output) = sample.split_once("}{").unwrap();
+            let output = output.split_once("}%").unwrap().0;
+            let tetrominoes = input
+                .split("\n\n")
+                .map(str::trim)
+                .filter(|&line| line != "0");
+            let actions = output.split("\n\n").map(str::trim);
+            let game_log = tetrominoes
+                .zip(actions)
+                .map(|(tetromino, actions)| {
+                    // The level 0 only announces the positions of its squares
+                    if level.reveals_tetromino_kind {
+                        format!("{}\n{}\n", tetromino, actions)
+                    } else {
+                        format!("O {}\n{}\n", tetromino, actions)
+                    }
+                })
+                .collect::<String>();
+            let report = replay::replay(&level, &game_log);
+            assert_eq!(
+                report.outcome,
+                replay::ReplayOutcome::Finished,
+                "{}",
+                report
+            );
+            assert_eq!(report.lines, sample_lines, "level {}", level_number);
+        }
     }
 }
@@ -0,0 +1,640 @@
+//! Re-simulation of a game log (see [`crate::game_log`]) with the rules of the interactor, so the
+//! logs written by solutions can be checked without playing a live game.
+
+use tetris_core::{
+    Action, BoardError, FallingTetromino, Gravity, Level, RotationSystem, Scoring, TetrisBoard,
+    Tetromino,
+};
+
+#[derive(Debug, PartialEq, Eq, derive_more::Display)]
+pub enum InvalidLine {
+    #[display(fmt = "expected a tetromino kind and its position")]
+    InvalidTetromino,
+    #[display(
+        fmt = "the tetromino does not fit into the board at the position {}",
+        _0
+    )]
+    PositionOutOfBoard(usize),
+    #[display(fmt = "the line of actions is missing")]
+    MissingActions,
+    #[display(fmt = "unknown command {:?}", _0)]
+    UnknownCommand(String),
+    #[display(fmt = "the tetromino cannot be held")]
+    IllegalHold,
+    #[display(fmt = "expected the held tetromino {} {}", _0, _1)]
+    NotHeldTetromino(tetris_core::TetrominoKind, usize),
+    #[display(fmt = "expected the column of the hole of the garbage row")]
+    InvalidGarbage,
+    #[display(fmt = "expected the size of the board of the level, {} {}", _0, _1)]
+    InvalidSize(usize, usize),
+    #[display(fmt = "expected the rotation system of the level, {}", _0)]
+    InvalidRotation(RotationSystem),
+    #[display(fmt = "expected the number of the rows of the starting board")]
+    InvalidBoardSize,
+    #[display(fmt = "the starting board is invalid: {}", _0)]
+    InvalidBoard(BoardError),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplayOutcome {
+    /// The log has ended while the game was still on.
+    Finished,
+    /// The tetromino could not get placed on the board (or the garbage has pushed the stack out
+    /// of the board).
+    Dead {
+        tetromino_number: u64,
+        line_number: usize,
+    },
+    /// The game would be stopped with PE on this line of the log.
+    InvalidLine {
+        line_number: usize,
+        line: String,
+        reason: InvalidLine,
+    },
+}
+
+pub struct ReplayReport {
+    pub outcome: ReplayOutcome,
+    pub lines: u64,
+    pub score: Scoring,
+    pub tetrominoes: u64,
+    /// Tetrominoes that the log has after the game is over.
+    pub extra_tetrominoes: u64,
+    /// The summary line the log ends with, if any.
+    pub summary: Option<String>,
+    pub board: TetrisBoard,
+}
+
+impl std::fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.board)?;
+        match &self.outcome {
+            ReplayOutcome::Finished => writeln!(f, "The game log has ended")?,
+            ReplayOutcome::Dead {
+                tetromino_number,
+                line_number,
+            } => writeln!(
+                f,
+                "Dead on the tetromino #{} (line {} of the log)",
+                tetromino_number, line_number
+            )?,
+            ReplayOutcome::InvalidLine {
+                line_number,
+                line,
+                reason,
+            } => writeln!(f, "Invalid line {} ({:?}): {}", line_number, line, reason)?,
+        }
+        writeln!(f, "Lines: {}", self.lines)?;
+        writeln!(f, "Score: {}", self.score.points)?;
+        writeln!(f, "Tetrominoes: {}", self.tetrominoes)?;
+        if self.extra_tetrominoes > 0 {
+            writeln!(
+                f,
+                "The log has {} more tetrominoes after the game is over",
+                self.extra_tetrominoes
+            )?;
+        }
+        if let Some(summary) = &self.summary {
+            writeln!(f, "The log claims: {}", summary)?;
+        }
+        Ok(())
+    }
+}
+
+/// The column of the hole (starting with 0) of the garbage row.
+fn parse_garbage(hole: &str, board: &TetrisBoard) -> Result<usize, InvalidLine> {
+    match hole.trim().parse::<usize>() {
+        Ok(hole) if (1..=board.width()).contains(&hole) => Ok(hole - 1),
+        _ => Err(InvalidLine::InvalidGarbage),
+    }
+}
+
+/// Whether the width and the height of the `size` line are the ones of the level.
+fn is_level_size(size: &str, level: &Level) -> bool {
+    let size = size
+        .split_ascii_whitespace()
+        .map(str::parse)
+        .collect::<Result<Vec<usize>, _>>();
+    size == Ok(vec![level.board_width, level.board_height])
+}
+
+/// The starting board with the given number of the rows that follow the `board` line.
+fn parse_board<'a>(
+    rows: &str,
+    log_lines: impl Iterator<Item = &'a str>,
+    level: &Level,
+) -> Result<TetrisBoard, InvalidLine> {
+    let rows = rows
+        .trim()
+        .parse()
+        .map_err(|_| InvalidLine::InvalidBoardSize)?;
+    TetrisBoard::parse_lines(log_lines.take(rows))
+        .and_then(|lines| TetrisBoard::with_lines(level.board_width, level.board_height, &lines))
+        .map_err(InvalidLine::InvalidBoard)
+}
+
+fn parse_tetromino(line: &str, board: &TetrisBoard) -> Result<Tetromino, InvalidLine> {
+    let mut tokens = line.split_ascii_whitespace();
+    let (kind, position) = match (tokens.next(), tokens.next(), tokens.next()) {
+        (Some(kind), Some(position), None) => (kind, position),
+        _ => return Err(InvalidLine::InvalidTetromino),
+    };
+    let tetromino = Tetromino {
+        kind: kind.parse().map_err(|()| InvalidLine::InvalidTetromino)?,
+        position: position
+            .parse()
+            .map_err(|_| InvalidLine::InvalidTetromino)?,
+        rotation: 0,
+    };
+    if tetromino.position < 1 || tetromino.position + tetromino.width() - 1 > board.width() {
+        return Err(InvalidLine::PositionOutOfBoard(tetromino.position));
+    }
+    Ok(tetromino)
+}
+
+pub fn replay(level: &Level, game_log: &str) -> ReplayReport {
+    let mut report = ReplayReport {
+        outcome: ReplayOutcome::Finished,
+        lines: 0,
+        score: Scoring::default(),
+        tetrominoes: 0,
+        extra_tetrominoes: 0,
+        summary: None,
+        board: level.new_board(),
+    };
+    let mut held_tetromino = None;
+    // The tetromino that the next one in the log must be, as it comes back from the hold
+    let mut returning_tetromino: Option<Tetromino> = None;
+    let mut has_held = false;
+    let mut log_lines = game_log
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .peekable();
+
+    if let Some((line_number, line)) = log_lines.next_if(|(_, line)| line.starts_with("size ")) {
+        if !is_level_size(&line["size ".len()..], level) {
+            report.outcome = ReplayOutcome::InvalidLine {
+                line_number,
+                line: line.to_owned(),
+                reason: InvalidLine::InvalidSize(level.board_width, level.board_height),
+            };
+            return report;
+        }
+    }
+    // The log of the classic rotation has no `rotation` line
+    let rotation = log_lines.next_if(|(_, line)| line.starts_with("rotation "));
+    let rotation_system = rotation.map_or(Ok(RotationSystem::Classic), |(_, line)| {
+        line["rotation ".len()..].trim().parse()
+    });
+    if rotation_system != Ok(level.rotation_system) {
+        let (line_number, line) = rotation
+            .or_else(|| log_lines.peek().copied())
+            .unwrap_or((1, ""));
+        report.outcome = ReplayOutcome::InvalidLine {
+            line_number,
+            line: line.to_owned(),
+            reason: InvalidLine::InvalidRotation(level.rotation_system),
+        };
+        return report;
+    }
+    if let Some((line_number, line)) = log_lines.next_if(|(_, line)| line.starts_with("board ")) {
+        let rows = log_lines.by_ref().map(|(_, row)| row);
+        match parse_board(&line["board ".len()..], rows, level) {
+            Ok(board) => report.board = board,
+            Err(reason) => {
+                report.outcome = ReplayOutcome::InvalidLine {
+                    line_number,
+                    line: line.to_owned(),
+                    reason,
+                };
+                return report;
+            }
+        }
+    }
+
+    while let Some((line_number, line)) = log_lines.next() {
+        if line.trim().is_empty() {
+            break;
+        }
+        let invalid_line = |line_number, line: &str, reason| ReplayOutcome::InvalidLine {
+            line_number,
+            line: line.to_owned(),
+            reason,
+        };
+        if let Some(hole) = line.strip_prefix("garbage ") {
+            match parse_garbage(hole, &report.board) {
+                Ok(hole) => {
+                    if report.board.raise_garbage(hole).is_err() {
+                        report.outcome = ReplayOutcome::Dead {
+                            tetromino_number: report.tetrominoes,
+                            line_number,
+                        };
+                        break;
+                    }
+                    continue;
+                }
+                Err(reason) => {
+                    report.outcome = invalid_line(line_number, line, reason);
+                    break;
+                }
+            }
+        }
+        let mut tetromino = match parse_tetromino(line, &report.board) {
+            Ok(tetromino) => tetromino,
+            Err(reason) => {
+                report.outcome = invalid_line(line_number, line, reason);
+                break;
+            }
+        };
+        match returning_tetromino.take() {
+            Some(held) if held.kind != tetromino.kind || held.position != tetromino.position => {
+                report.outcome = invalid_line(
+                    line_number,
+                    line,
+                    InvalidLine::NotHeldTetromino(held.kind, held.position),
+                );
+                break;
+            }
+            Some(_) => {}
+            None => report.tetrominoes += 1,
+        }
+
+        let (actions_line_number, actions) = match log_lines.next() {
+            Some(actions) => actions,
+            None => {
+                report.outcome = invalid_line(line_number, line, InvalidLine::MissingActions);
+                break;
+            }
+        };
+        let mut falling_tetromino = match level.gravity {
+            Gravity::Drop => None,
+            Gravity::Step => {
+                match FallingTetromino::spawn(&report.board, tetromino, level.rotation_system) {
+                    Ok(falling_tetromino) => Some(falling_tetromino),
+                    Err(_) => {
+                        report.outcome = ReplayOutcome::Dead {
+                            tetromino_number: report.tetrominoes,
+                            line_number,
+                        };
+                        break;
+                    }
+                }
+            }
+        };
+        let mut parsed_actions = match actions
+            .split_ascii_whitespace()
+            .map(|action| action.parse::<Action>().map_err(|()| action))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(parsed_actions) => parsed_actions,
+            Err(unknown_command) => {
+                report.outcome = invalid_line(
+                    actions_line_number,
+                    actions,
+                    InvalidLine::UnknownCommand(unknown_command.to_owned()),
+                );
+                break;
+            }
+        };
+        // With the step-by-step gravity the tetromino lands when it cannot move down, and the
+        // rest of the actions are ignored
+        if let Some(falling_tetromino) = falling_tetromino.as_mut() {
+            let landing_index = parsed_actions.iter().position(|&action| {
+                action != Action::Hold
+                    && !falling_tetromino.apply_action(action, &report.board)
+                    && action == Action::Down
+            });
+            if let Some(landing_index) = landing_index {
+                parsed_actions.truncate(landing_index + 1);
+            }
+        }
+        // The interactor logs the tetromino that replaces the held one right after the hold
+        if let Some(hold_index) = parsed_actions.iter().position(|&a| a == Action::Hold) {
+            if !level.allows_hold || has_held || hold_index + 1 != parsed_actions.len() {
+                report.outcome =
+                    invalid_line(actions_line_number, actions, InvalidLine::IllegalHold);
+                break;
+            }
+            has_held = true;
+            returning_tetromino = held_tetromino.replace(tetromino);
+            continue;
+        }
+        has_held = false;
+
+        match falling_tetromino.as_mut() {
+            Some(falling_tetromino) => {
+                falling_tetromino.lock(&mut report.board);
+            }
+            None => {
+                if parsed_actions.contains(&Action::Down) {
+                    report.outcome = invalid_line(
+                        actions_line_number,
+                        actions,
+                        InvalidLine::UnknownCommand(Action::Down.to_string()),
+                    );
+                    break;
+                }
+                for &action in &parsed_actions {
+                    if action == Action::Rotate {
+                        tetromino.rotate(level.rotation_system, report.board.width());
+                    } else {
+                        tetromino.apply_action(action, report.board.width());
+                    }
+                }
+                if report.board.try_apply_tetromino(tetromino).is_err() {
+                    report.outcome = ReplayOutcome::Dead {
+                        tetromino_number: report.tetrominoes,
+                        line_number,
+                    };
+                    break;
+                }
+            }
+        }
+        let cleaned_lines = report.board.clean_full_lines();
+        report.lines += cleaned_lines;
+        report.score.record(cleaned_lines, report.board.is_empty());
+    }
+
+    if report.outcome != ReplayOutcome::Finished {
+        let remaining_moves = log_lines
+            .by_ref()
+            .take_while(|(_, line)| !line.trim().is_empty())
+            .filter(|(_, line)| !line.starts_with("garbage "))
+            .count();
+        report.extra_tetrominoes = (remaining_moves as u64 + 1) / 2;
+    }
+    report.summary = log_lines
+        .map(|(_, line)| line.trim())
+        .find(|line| line.starts_with('#'))
+        .map(|line| line.trim_start_matches('#').trim().to_owned());
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_lines() {
+        let report = replay(
+            &Level::default(),
+            "O 1\n\nO 3\n\nO 5\n\nO 4\nshift_right shift_right shift_right\nO 5\nshift_right \
+             shift_right shift_right shift_right shift_right\n\n# Ok. Lines: 2. Score: 1500. \
+             Tetrominoes: 5\n",
+        );
+        assert_eq!(report.outcome, ReplayOutcome::Finished);
+        assert_eq!(report.lines, 2);
+        // A double that empties the board
+        assert_eq!(report.score.points, 300 + 1200);
+        assert_eq!(report.tetrominoes, 5);
+        assert_eq!(report.extra_tetrominoes, 0);
+        assert_eq!(
+            report.summary.as_deref(),
+            Some("Ok. Lines: 2. Score: 1500. Tetrominoes: 5")
+        );
+    }
+
+    #[test]
+    fn test_replay_dead() {
+        let report = replay(
+            &Level::builtin(9).unwrap(),
+            &"I 3\nshift_left shift_left\n".repeat(7),
+        );
+        assert_eq!(
+            report.outcome,
+            ReplayOutcome::Dead {
+                tetromino_number: 6,
+                line_number: 11
+            }
+        );
+        assert_eq!(report.lines, 0);
+        assert_eq!(report.tetrominoes, 6);
+        assert_eq!(report.extra_tetrominoes, 1);
+        assert_eq!(report.summary, None);
+    }
+
+    #[test]
+    fn test_replay_garbage() {
+        let level = Level::builtin(9).unwrap();
+        let report = replay(
+            &level,
+            "I 3\nshift_left shift_left\ngarbage 2\ngarbage 10\n",
+        );
+        assert_eq!(report.outcome, ReplayOutcome::Finished);
+        assert_eq!(report.board.to_string().matches('#').count(), 4 + 9 + 9);
+
+        // The garbage pushes the tetromino out of the top
+        let report = replay(
+            &level,
+            &format!("I 3\nshift_left shift_left\n{}", "garbage 1\n".repeat(18)),
+        );
+        assert_eq!(
+            report.outcome,
+            ReplayOutcome::Dead {
+                tetromino_number: 1,
+                line_number: 19
+            }
+        );
+        assert_eq!(report.extra_tetrominoes, 0);
+
+        let report = replay(&level, "I 3\n\ngarbage 11\n");
+        assert_eq!(
+            report.outcome,
+            ReplayOutcome::InvalidLine {
+                line_number: 3,
+                line: "garbage 11".into(),
+                reason: InvalidLine::InvalidGarbage
+            }
+        );
+    }
+
+    #[test]
+    fn test_replay_board() {
+        let level = Level::builtin(9).unwrap();
+        let report = replay(
+            &level,
+            "board 3\n|       #  |\n|########  |\n|########  |\nO 5\nshift_right shift_right shift_right shift_right\n",
+        );
+        assert_eq!(report.outcome, ReplayOutcome::Finished);
+        assert_eq!(report.lines, 2);
+        assert_eq!(report.board.to_string().matches('#').count(), 1);
+
+        let report = replay(&level, "board 1\n|##########|\nI 3\n\n");
+        assert_eq!(
+            report.outcome,
+            ReplayOutcome::InvalidLine {
+                line_number: 1,
+                line: "board 1".into(),
+                reason: InvalidLine::InvalidBoard(BoardError::FullRow("|##########|".into()))
+            }
+        );
+
+        let report = replay(&level, "board two\n");
+        assert_eq!(
+            report.outcome,
+            ReplayOutcome::InvalidLine {
+                line_number: 1,
+                line: "board two".into(),
+                reason: InvalidLine::InvalidBoardSize
+            }
+        );
+
+        let report = replay(&level, "size 10 20\nboard 1\n|########  |\nO 9\n\n");
+        assert_eq!(report.outcome, ReplayOutcome::Finished);
+        assert_eq!(report.lines, 1);
+
+        let report = replay(&level, "size 8 6\nO 1\n\n");
+        assert_eq!(
+            report.outcome,
+            ReplayOutcome::InvalidLine {
+                line_number: 1,
+                line: "size 8 6".into(),
+                reason: InvalidLine::InvalidSize(10, 20)
+            }
+        );
+    }
+
+    #[test]
+    fn test_replay_rotation() {
+        let level = Level {
+            rotation_system: RotationSystem::Srs,
+            ..Level::builtin(9).unwrap()
+        };
+        let report = replay(&level, "rotation srs\nI 1\nrotate\n\n");
+        assert_eq!(report.outcome, ReplayOutcome::Finished);
+        assert_eq!(report.tetrominoes, 1);
+
+        assert_eq!(
+            replay(&level, "I 1\nrotate\n\n").outcome,
+            ReplayOutcome::InvalidLine {
+                line_number: 1,
+                line: "I 1".into(),
+                reason: InvalidLine::InvalidRotation(RotationSystem::Srs)
+            }
+        );
+        assert_eq!(
+            replay(&Level::builtin(9).unwrap(), "rotation srs\nI 1\n\n").outcome,
+            ReplayOutcome::InvalidLine {
+                line_number: 1,
+                line: "rotation srs".into(),
+                reason: InvalidLine::InvalidRotation(RotationSystem::Classic)
+            }
+        );
+    }
+
+    #[test]
+    fn test_replay_hold() {
+        let level = Level {
+            allows_hold: true,
+            ..Level::builtin(9).unwrap()
+        };
+        let report = replay(
+            &level,
+            "I 3\nhold\nO 1\n\nT 5\nhold\nI 3\nshift_left shift_left\nO 9\nhold\nT 5\n\n",
+        );
+        assert_eq!(report.outcome, ReplayOutcome::Finished);
+        assert_eq!(report.tetrominoes, 4);
+        assert_eq!(report.board.to_string().matches('#').count(), 12);
+
+        for &(game_log, line_number, ref reason) in &[
+            ("I 3\nhold\nO 1\nhold\n", 4, InvalidLine::IllegalHold),
+            ("I 3\nhold rotate\n", 2, InvalidLine::IllegalHold),
+            (
+                "I 3\nhold\nO 1\n\nT 5\nhold\nI 4\n\n",
+                7,
+                InvalidLine::NotHeldTetromino(tetris_core::TetrominoKind::I, 3),
+            ),
+        ] {
+            match replay(&level, game_log).outcome {
+                ReplayOutcome::InvalidLine {
+                    line_number: invalid_line_number,
+                    reason: invalid_line_reason,
+                    ..
+                } => {
+                    assert_eq!(invalid_line_number, line_number, "{:?}", game_log);
+                    assert_eq!(&invalid_line_reason, reason, "{:?}", game_log);
+                }
+                outcome => panic!("{:?} is replayed as {:?}", game_log, outcome),
+            }
+        }
+        assert_eq!(
+            replay(&Level::builtin(9).unwrap(), "I 3\nhold\n").outcome,
+            ReplayOutcome::InvalidLine {
+                line_number: 2,
+                line: "hold".into(),
+                reason: InvalidLine::IllegalHold
+            }
+        );
+    }
+
+    #[test]
+    fn test_replay_step_gravity() {
+        let level = Level {
+            gravity: Gravity::Step,
+            board_width: 8,
+            board_height: 6,
+            ..Level::builtin(9).unwrap()
+        };
+        // The first O is tucked under the I, and the others clear the two bottom lines
+        let report = replay(
+            &level,
+            "O 7\n\nI 5\nrotate\nO 1\ndown down down down shift_right shift_right shift_right \
+             shift_right down\nO 1\n\nO 3\n\n",
+        );
+        assert_eq!(report.outcome, ReplayOutcome::Finished);
+        assert_eq!(report.lines, 2);
+        assert_eq!(report.tetrominoes, 5);
+
+        // The actions after the landing are ignored, even `hold`
+        let report = replay(
+            &level,
+            "I 1\ndown down down down down down shift_right hold\nO 3\n\n",
+        );
+        assert_eq!(report.outcome, ReplayOutcome::Finished);
+        assert_eq!(report.tetrominoes, 2);
+        let bottom_line = &report.board.lines()[0];
+        assert_eq!(
+            (0..8)
+                .filter(|&column| bottom_line[column].is_occupied())
+                .collect::<Vec<_>>(),
+            vec![0, 2, 3]
+        );
+        assert_eq!(
+            replay(&Level::builtin(9).unwrap(), "I 1\ndown\n").outcome,
+            ReplayOutcome::InvalidLine {
+                line_number: 2,
+                line: "down".into(),
+                reason: InvalidLine::UnknownCommand("down".into())
+            }
+        );
+    }
+
+    #[test]
+    fn test_replay_invalid_lines() {
+        let level = Level::builtin(9).unwrap();
+        for &(game_log, line_number, ref reason) in &[
+            (
+                "O 1\nshift_left drop\n",
+                2,
+                InvalidLine::UnknownCommand("drop".into()),
+            ),
+            ("O 1\n\nT 9\n\n", 3, InvalidLine::PositionOutOfBoard(9)),
+            ("O 1\n\nX 1\n\n", 3, InvalidLine::InvalidTetromino),
+            ("O\n\n", 1, InvalidLine::InvalidTetromino),
+            ("O 1\n\nO 1", 3, InvalidLine::MissingActions),
+        ] {
+            match replay(&level, game_log).outcome {
+                ReplayOutcome::InvalidLine {
+                    line_number: invalid_line_number,
+                    reason: invalid_line_reason,
+                    ..
+                } => {
+                    assert_eq!(invalid_line_number, line_number, "{:?}", game_log);
+                    assert_eq!(&invalid_line_reason, reason, "{:?}", game_log);
+                }
+                outcome => panic!("{:?} is replayed as {:?}", game_log, outcome),
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! The report of the game for the contestants: what has gone wrong and where. It is printed to
+//! stderr, and it can be written as JSON for the tools:
+//!
+//! ```json
+//! {
+//!   "verdict": "wrong output format",
+//!   "exit_code": 2,
+//!   "game_over": "WrongInput",
+//!   "lines": 0,
+//!   "score": 0,
+//!   "tetrominoes": 1,
+//!   "failure": {
+//!     "reason": "unknown command \"rotat\"",
+//!     "move": 1,
+//!     "line": "shift_left rotat",
+//!     "token": "rotat",
+//!     "tetromino": { "kind": "T", "position": 4, "rotation": 0 },
+//!     "placement": null,
+//!     "board": ["|          |", "...", "____________"]
+//!   }
+//! }
+//! ```
+
+use serde::Serialize;
+use tetris_core::Tetromino;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TetrominoReport {
+    pub kind: String,
+    pub position: usize,
+    pub rotation: usize,
+}
+
+impl From<&Tetromino> for TetrominoReport {
+    fn from(tetromino: &Tetromino) -> Self {
+        Self {
+            kind: tetromino.kind.to_string(),
+            position: tetromino.position,
+            rotation: tetromino.rotation % 360,
+        }
+    }
+}
+
+impl std::fmt::Display for TetrominoReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} (rotated by {})",
+            self.kind, self.position, self.rotation
+        )
+    }
+}
+
+/// What has ended the game before the goal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Failure {
+    pub reason: String,
+    /// The number of the line of actions (the same as in the game log).
+    #[serde(rename = "move")]
+    pub move_number: u64,
+    /// The line of actions that has failed.
+    pub line: Option<String>,
+    /// The action that has failed.
+    pub token: Option<String>,
+    pub tetromino: Option<TetrominoReport>,
+    /// Where the tetromino has not fit into the board.
+    pub placement: Option<TetrominoReport>,
+    /// The lines of the board from the top at the time of the failure.
+    pub board: Vec<String>,
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Move {}: {}", self.move_number, self.reason)?;
+        if let Some(line) = &self.line {
+            writeln!(f, "Line: {:?}", line)?;
+        }
+        if let Some(token) = &self.token {
+            writeln!(f, "Token: {:?}", token)?;
+        }
+        if let Some(tetromino) = &self.tetromino {
+            writeln!(f, "Tetromino: {}", tetromino)?;
+        }
+        if let Some(placement) = &self.placement {
+            writeln!(f, "Failed placement: {}", placement)?;
+        }
+        writeln!(f, "Board:")?;
+        for line in &self.board {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub verdict: String,
+    pub exit_code: i32,
+    pub game_over: String,
+    pub lines: u64,
+    pub score: u64,
+    pub tetrominoes: u64,
+    pub failure: Option<Failure>,
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::TetrominoKind;
+
+    use super::*;
+
+    #[test]
+    fn test_failure() {
+        let tetromino = Tetromino {
+            kind: TetrominoKind::T,
+            position: 2,
+            rotation: 450,
+        };
+        let failure = Failure {
+            reason: "unknown command \"rotat\"".into(),
+            move_number: 3,
+            line: Some("shift_left rotat".into()),
+            token: Some("rotat".into()),
+            tetromino: Some((&tetromino).into()),
+            placement: None,
+            board: vec!["|#   |".into(), "______".into()],
+        };
+        assert_eq!(
+            failure.to_string(),
+            "Move 3: unknown command \"rotat\"\nLine: \"shift_left rotat\"\nToken: \"rotat\"\n\
+             Tetromino: T 2 (rotated by 90)\nBoard:\n|#   |\n______\n"
+        );
+
+        let report = Report {
+            verdict: "wrong output format".into(),
+            exit_code: 2,
+            game_over: "WrongInput".into(),
+            lines: 0,
+            score: 0,
+            tetrominoes: 3,
+            failure: Some(failure),
+        };
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["failure"]["move"], 3);
+        assert_eq!(json["failure"]["tetromino"]["rotation"], 90);
+        assert_eq!(json["failure"]["placement"], serde_json::Value::Null);
+        assert_eq!(json["verdict"], "wrong output format");
+    }
+}
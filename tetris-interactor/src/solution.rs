@@ -0,0 +1,257 @@
+//! The responses of the solution. The lines are read from stdin in the background, so the
+//! interactor can time every response and stop waiting for the solution once it runs out of
+//! time. The lines are bounded in length, so a solution cannot flood the memory of the
+//! interactor.
+
+use std::io::{BufRead, Read};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLimits {
+    /// The time that the solution may think about a single tetromino.
+    pub per_move: Option<Duration>,
+    /// The total time that the solution may think during the game.
+    pub per_game: Option<Duration>,
+}
+
+/// The default limit of the length of a line of the solution, in bytes.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 16 * 1024;
+
+#[derive(Debug, derive_more::Display)]
+pub enum ReadError {
+    #[display(fmt = "the solution has closed its stdout")]
+    EndOfInput,
+    #[display(fmt = "reading from the solution failed: {}", _0)]
+    Io(std::io::Error),
+    #[display(fmt = "the line of the solution is longer than {} bytes", _0)]
+    LineTooLong(usize),
+    #[display(fmt = "the solution has exceeded the time limit per move")]
+    MoveTimeLimitExceeded,
+    #[display(fmt = "the solution has exceeded the time limit per game")]
+    GameTimeLimitExceeded,
+}
+
+pub struct Solution {
+    lines: mpsc::Receiver<Result<String, ReadError>>,
+    time_limits: TimeLimits,
+    /// How long the solution has taken to respond to every line that the interactor has sent.
+    response_times: Vec<Duration>,
+}
+
+impl Solution {
+    /// Starts reading the responses of the solution from stdin.
+    pub fn from_stdin(time_limits: TimeLimits, max_line_length: usize) -> Self {
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            let stdin = std::io::stdin();
+            send_lines(stdin.lock(), max_line_length, &sender);
+        });
+        Self::new(lines, time_limits)
+    }
+
+    fn new(lines: mpsc::Receiver<Result<String, ReadError>>, time_limits: TimeLimits) -> Self {
+        Self {
+            lines,
+            time_limits,
+            response_times: Vec::new(),
+        }
+    }
+
+    /// Waits for the next line of the solution, and times the response.
+    pub fn read_line(&mut self, line: &mut String) -> Result<(), ReadError> {
+        let spent_time = self.spent_time();
+        let game_time_left = self
+            .time_limits
+            .per_game
+            .map(|per_game| per_game.checked_sub(spent_time).unwrap_or_default());
+        let timeout = match (self.time_limits.per_move, game_time_left) {
+            (Some(per_move), Some(game_time_left)) => Some(per_move.min(game_time_left)),
+            (per_move, game_time_left) => per_move.or(game_time_left),
+        };
+
+        let start = Instant::now();
+        let received = match timeout {
+            Some(timeout) => self.lines.recv_timeout(timeout),
+            None => self
+                .lines
+                .recv()
+                .map_err(|mpsc::RecvError| mpsc::RecvTimeoutError::Disconnected),
+        };
+        self.response_times.push(start.elapsed());
+        match received {
+            Ok(received_line) => {
+                line.push_str(&received_line?);
+                Ok(())
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(ReadError::EndOfInput),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if timeout == self.time_limits.per_move {
+                    Err(ReadError::MoveTimeLimitExceeded)
+                } else {
+                    Err(ReadError::GameTimeLimitExceeded)
+                }
+            }
+        }
+    }
+
+    /// The total time that the solution has spent on its responses.
+    pub fn spent_time(&self) -> Duration {
+        self.response_times.iter().sum()
+    }
+
+    /// The breakdown of the response times for the game log: the totals and the slowest moves
+    /// (numbered in the order of the lines of actions in the game log).
+    pub fn timing_report(&self, slowest_moves: usize) -> Vec<String> {
+        let max = match self.response_times.iter().max() {
+            Some(max) => max,
+            None => return Vec::new(),
+        };
+        let mut moves: Vec<_> = self.response_times.iter().enumerate().collect();
+        moves.sort_by(|(_, a), (_, b)| b.cmp(a));
+        vec![
+            format!(
+                "Response time: total {:.3} s, mean {:.3} ms, max {:.3} ms",
+                self.spent_time().as_secs_f64(),
+                self.spent_time().as_secs_f64() * 1000.0 / self.response_times.len() as f64,
+                max.as_secs_f64() * 1000.0
+            ),
+            format!(
+                "Slowest moves: {}",
+                moves
+                    .iter()
+                    .take(slowest_moves)
+                    .map(|(index, time)| format!(
+                        "#{} {:.3} ms",
+                        index + 1,
+                        time.as_secs_f64() * 1000.0
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ]
+    }
+}
+
+/// Sends the lines of the reader to the channel till the end of the input or the first error
+/// (the lines that are longer than the limit are not read into memory in full).
+fn send_lines(
+    mut reader: impl BufRead,
+    max_line_length: usize,
+    sender: &mpsc::Sender<Result<String, ReadError>>,
+) {
+    loop {
+        let mut line = Vec::new();
+        // The limit excludes the line break
+        let line = match (&mut reader)
+            .take(max_line_length as u64 + 1)
+            .read_until(b'\n', &mut line)
+        {
+            Ok(0) => break,
+            Ok(length) if length > max_line_length && !line.ends_with(b"\n") => {
+                Err(ReadError::LineTooLong(max_line_length))
+            }
+            Ok(_) => String::from_utf8(line).map_err(|error| {
+                ReadError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+            }),
+            Err(error) => Err(ReadError::Io(error)),
+        };
+        let is_last = line.is_err();
+        if sender.send(line).is_err() || is_last {
+            return;
+        }
+    }
+    debug!("The solution has closed its stdout");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piped_solution(
+        time_limits: TimeLimits,
+    ) -> (mpsc::Sender<Result<String, ReadError>>, Solution) {
+        let (sender, lines) = mpsc::channel();
+        (sender, Solution::new(lines, time_limits))
+    }
+
+    #[test]
+    fn test_read_line() {
+        let (sender, mut solution) = piped_solution(TimeLimits::default());
+        sender.send(Ok("rotate\n".into())).unwrap();
+        let mut line = String::new();
+        solution.read_line(&mut line).unwrap();
+        assert_eq!(line, "rotate\n");
+
+        drop(sender);
+        line.clear();
+        assert!(matches!(
+            solution.read_line(&mut line),
+            Err(ReadError::EndOfInput)
+        ));
+        assert_eq!(line, "");
+        assert_eq!(solution.response_times.len(), 2);
+    }
+
+    fn sent_lines(input: &[u8], max_line_length: usize) -> Vec<Result<String, String>> {
+        let (sender, lines) = mpsc::channel();
+        send_lines(input, max_line_length, &sender);
+        drop(sender);
+        lines
+            .iter()
+            .map(|line| line.map_err(|error| error.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_send_lines() {
+        assert_eq!(
+            sent_lines(b"rotate\nshift_left\n\ndown", 10),
+            vec![
+                Ok("rotate\n".into()),
+                Ok("shift_left\n".into()),
+                Ok("\n".into()),
+                Ok("down".into())
+            ]
+        );
+        assert_eq!(
+            sent_lines(b"rotate\nshift_left\nrotate\n", 6),
+            vec![
+                Ok("rotate\n".into()),
+                Err("the line of the solution is longer than 6 bytes".into())
+            ]
+        );
+        assert!(matches!(
+            sent_lines(b"rotate \xff\nrotate\n", 100).as_slice(),
+            [Err(error)] if error.starts_with("reading from the solution failed")
+        ));
+    }
+
+    #[test]
+    fn test_time_limits() {
+        let (_sender, mut solution) = piped_solution(TimeLimits {
+            per_move: Some(Duration::from_millis(20)),
+            per_game: None,
+        });
+        assert!(matches!(
+            solution.read_line(&mut String::new()),
+            Err(ReadError::MoveTimeLimitExceeded)
+        ));
+        assert!(solution.spent_time() >= Duration::from_millis(20));
+
+        let (sender, mut solution) = piped_solution(TimeLimits {
+            per_move: Some(Duration::from_secs(10)),
+            per_game: Some(Duration::from_millis(30)),
+        });
+        sender.send(Ok("\n".into())).unwrap();
+        solution.read_line(&mut String::new()).unwrap();
+        assert!(matches!(
+            solution.read_line(&mut String::new()),
+            Err(ReadError::GameTimeLimitExceeded)
+        ));
+        assert_eq!(solution.timing_report(1).len(), 2);
+        assert!(solution.timing_report(1)[1].starts_with("Slowest moves: #2 "));
+    }
+}
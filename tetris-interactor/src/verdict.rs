@@ -0,0 +1,128 @@
+//! Grading of the games that have not reached the goal, as the percent of the points for the test
+//! that the verdict reports with the testlib "partially correct" codes.
+
+#[derive(Debug, PartialEq, Eq, derive_more::Display)]
+pub enum GradingError {
+    #[display(fmt = "unknown grading {:?}", _0)]
+    UnknownGrading(String),
+    #[display(
+        fmt = "invalid tier {:?} (expected `<goal>:<percent>` with the percent below 100)",
+        _0
+    )]
+    InvalidTier(String),
+}
+
+/// How a game that has not reached the goal is graded.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Grading {
+    /// Anything short of the goal is a wrong answer.
+    #[default]
+    AllOrNothing,
+    /// The percent of the goal that the game has reached.
+    Proportional,
+    /// The percent of the highest reached tier, as `(goal, percent)` pairs (only the goal itself
+    /// gets 100 percent, which is the plain OK).
+    Tiers(Vec<(u64, u8)>),
+}
+
+impl std::str::FromStr for Grading {
+    type Err = GradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split_ascii_whitespace();
+        Ok(match tokens.next() {
+            Some("all-or-nothing") if tokens.next().is_none() => Self::AllOrNothing,
+            Some("proportional") if tokens.next().is_none() => Self::Proportional,
+            Some("tiers") => Self::Tiers(
+                tokens
+                    .map(|tier| {
+                        let invalid_tier = || GradingError::InvalidTier(tier.to_owned());
+                        let (goal, percent) = tier.split_once(':').ok_or_else(invalid_tier)?;
+                        match (goal.parse(), percent.parse()) {
+                            (Ok(goal), Ok(percent)) if percent < 100 => Ok((goal, percent)),
+                            _ => Err(invalid_tier()),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(GradingError::UnknownGrading(s.to_owned())),
+        })
+    }
+}
+
+impl Grading {
+    /// The percent for reaching the given progress (lines or score) towards the goal.
+    pub fn percent(&self, progress: u64, goal: u64) -> u8 {
+        if progress >= goal {
+            return 100;
+        }
+        match self {
+            Self::AllOrNothing => 0,
+            Self::Proportional => (progress * 100 / goal) as u8,
+            Self::Tiers(tiers) => tiers
+                .iter()
+                .filter(|&&(tier_goal, _)| progress >= tier_goal)
+                .map(|&(_, percent)| percent)
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// The percent for reaching the goal of the sprint with the given number of the locked
+/// tetrominoes: all the points at the par, and less the more tetrominoes the game has taken.
+pub fn sprint_percent(par: u64, tetrominoes: u64) -> u8 {
+    (par * 100 / tetrominoes.max(1)).clamp(1, 100) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grading() {
+        assert_eq!(Grading::AllOrNothing.percent(9, 10), 0);
+        assert_eq!(Grading::AllOrNothing.percent(10, 10), 100);
+        assert_eq!(Grading::Proportional.percent(9, 10), 90);
+        assert_eq!(Grading::Proportional.percent(1, 3), 33);
+        assert_eq!(Grading::Proportional.percent(12, 10), 100);
+
+        let tiers: Grading = "tiers 3:20 6:50 9:80".parse().unwrap();
+        assert_eq!(tiers, Grading::Tiers(vec![(3, 20), (6, 50), (9, 80)]));
+        for &(progress, percent) in &[(0, 0), (2, 0), (3, 20), (8, 50), (9, 80), (10, 100)] {
+            assert_eq!(tiers.percent(progress, 10), percent, "{}", progress);
+        }
+    }
+
+    #[test]
+    fn test_sprint_percent() {
+        assert_eq!(sprint_percent(20, 20), 100);
+        assert_eq!(sprint_percent(20, 40), 50);
+        assert_eq!(sprint_percent(20, 200), 10);
+        assert_eq!(sprint_percent(20, 19), 100);
+        assert_eq!(sprint_percent(1, 1000), 1);
+    }
+
+    #[test]
+    fn test_parse_grading() {
+        assert_eq!("proportional".parse(), Ok(Grading::Proportional));
+        assert_eq!("all-or-nothing".parse(), Ok(Grading::AllOrNothing));
+        assert_eq!(
+            "tiers 3:20 6".parse::<Grading>(),
+            Err(GradingError::InvalidTier("6".into()))
+        );
+        assert_eq!(
+            "tiers 3:120".parse::<Grading>(),
+            Err(GradingError::InvalidTier("3:120".into()))
+        );
+        // A tier short of the goal cannot be worth all the points
+        assert_eq!(
+            "tiers 3:50 6:100".parse::<Grading>(),
+            Err(GradingError::InvalidTier("6:100".into()))
+        );
+        assert_eq!(
+            "proportional 3".parse::<Grading>(),
+            Err(GradingError::UnknownGrading("proportional 3".into()))
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Plays the tests of a problem with a solution the same way as DDOTS does: the interactor gets
+//! the answer file of the test as `answer.txt` in its working directory, and its stdin and stdout
+//! are connected to the stdout and stdin of the solution.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use structopt::StructOpt;
+use tetris_core::{parse_seconds, Verdict};
+use tracing::debug;
+
+mod problem;
+
+use problem::{Problem, Test};
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// The problem directory with Problem.xml (e.g. `problem-level-9`)
+    #[structopt(long, parse(from_os_str))]
+    problem_root: PathBuf,
+    /// The interactor executable (defaults to tetris-interactor next to tetris-runner)
+    #[structopt(long, parse(from_os_str))]
+    interactor: Option<PathBuf>,
+    /// The directory to keep the game logs of the tests in (`01.log`, `02.log`, ...)
+    #[structopt(long, parse(from_os_str))]
+    game_logs: Option<PathBuf>,
+    /// The time that the solution may think about a single tetromino, in seconds
+    #[structopt(long, parse(try_from_str = parse_seconds))]
+    move_time_limit: Option<Duration>,
+    /// The total time that the solution may think during a game, in seconds (defaults to the time
+    /// limit of the problem, which DDOTS applies to the whole run of the solution, i.e. the game)
+    #[structopt(long, parse(try_from_str = parse_seconds))]
+    game_time_limit: Option<Duration>,
+    /// What the interactor sends back after every locked tetromino for debugging: `none`,
+    /// `landing`, or `board` (defaults to the feedback of the tests)
+    #[structopt(long)]
+    feedback: Option<String>,
+    /// The file with the tetrominoes to give out in every test instead of the random ones, a
+    /// `kind position` pair per line
+    #[structopt(long, parse(from_os_str))]
+    sequence: Option<PathBuf>,
+    /// What happens when the sequence ends: `stop`, `repeat`, or `random` (defaults to `stop`)
+    #[structopt(long)]
+    sequence_end: Option<String>,
+    /// Show the logs of the interactor and the stderr of the solution
+    #[structopt(long)]
+    verbose: bool,
+    /// The solution executable with its arguments
+    #[structopt(required = true)]
+    solution: Vec<String>,
+}
+
+/// The result of a test: the verdict of the interactor, or the exit code of the interactor that
+/// has failed to give one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+enum TestResult {
+    #[display(fmt = "{}", _0)]
+    Verdict(Verdict),
+    #[display(fmt = "interactor failure (exit code {:?})", _0)]
+    InteractorFailure(Option<i32>),
+}
+
+impl TestResult {
+    /// The result of the test by the exit code of the interactor and its result file (the time
+    /// limit is reported as the wrong answer, and only the result line tells them apart).
+    fn new(exit_code: Option<i32>, result: &str) -> Self {
+        exit_code
+            .and_then(|exit_code| Verdict::from_result(exit_code, result))
+            .map_or(Self::InteractorFailure(exit_code), Self::Verdict)
+    }
+
+    /// The points of the test that the result gets.
+    fn points(self, test_points: u32) -> u32 {
+        match self {
+            Self::Verdict(verdict) => verdict.points(test_points),
+            Self::InteractorFailure(_) => 0,
+        }
+    }
+}
+
+fn main() {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing_subscriber::filter::LevelFilter::INFO)
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let mut opts = Opts::from_args();
+
+    let problem_file = opts.problem_root.join("Problem.xml");
+    let problem: Problem = std::fs::read_to_string(&problem_file)
+        .unwrap_or_else(|error| panic!("{:?} cannot be read: {}", problem_file, error))
+        .parse()
+        .unwrap_or_else(|error| panic!("{:?} is invalid: {}", problem_file, error));
+
+    let interactor = match opts.interactor.clone() {
+        Some(interactor) => interactor,
+        None => std::env::current_exe()
+            .expect("the path of tetris-runner is unknown")
+            .with_file_name(format!("tetris-interactor{}", std::env::consts::EXE_SUFFIX)),
+    };
+    // The interactor runs in the working directory of the test, so the relative paths would break
+    let interactor = interactor
+        .canonicalize()
+        .unwrap_or_else(|error| panic!("The interactor {:?} is not found: {}", interactor, error));
+    if let Some(sequence) = opts.sequence.take() {
+        opts.sequence = Some(sequence.canonicalize().unwrap_or_else(|error| {
+            panic!("The sequence file {:?} is not found: {}", sequence, error)
+        }));
+    }
+    let game_logs = opts.game_logs.as_ref().map(|game_logs| {
+        std::fs::create_dir_all(game_logs)
+            .and_then(|()| game_logs.canonicalize())
+            .unwrap_or_else(|error| {
+                panic!("The directory {:?} cannot be created: {}", game_logs, error)
+            })
+    });
+    let test_dir = std::env::temp_dir().join(format!("tetris-runner-{}", std::process::id()));
+    std::fs::create_dir_all(&test_dir)
+        .unwrap_or_else(|error| panic!("{:?} cannot be created: {}", test_dir, error));
+    // DDOTS limits the whole run of the solution with the time limit of the problem, so it is the
+    // budget of the game unless it is overridden (the thinking time is measured by the wall clock,
+    // which only gets closer to the CPU time of the judge the fewer other processes run)
+    let time_limits = TimeLimits {
+        per_move: opts.move_time_limit,
+        per_game: opts.game_time_limit.or(problem.time_limit),
+    };
+
+    let mut points = 0;
+    let mut all_ok = true;
+    for (index, test) in problem.tests.iter().enumerate() {
+        let number = index + 1;
+        let game_log = match game_logs {
+            Some(ref game_logs) => game_logs.join(format!("{:02}.log", number)),
+            None => test_dir.join("game.log"),
+        };
+        let result = run_test(&opts, &interactor, time_limits, test, &test_dir, &game_log);
+        let summary = read_summary(&game_log);
+        let test_points = result.points(test.points);
+        println!(
+            "Test {:02}: {} ({}/{} points). {}",
+            number,
+            result,
+            test_points,
+            test.points,
+            summary.first().map_or("", String::as_str)
+        );
+        // The response times of the solution
+        for comment in summary.iter().skip(1) {
+            println!("    {}", comment);
+        }
+        points += test_points;
+        all_ok &= result == TestResult::Verdict(Verdict::Ok);
+    }
+    println!("Total: {}/{} points", points, problem.total_points());
+
+    if let Err(error) = std::fs::remove_dir_all(&test_dir) {
+        debug!("{:?} cannot be removed: {}", test_dir, error);
+    }
+    std::process::exit(if all_ok { 0 } else { 1 });
+}
+
+/// The time limits that the runner passes to the interactor.
+#[derive(Debug, Clone, Copy)]
+struct TimeLimits {
+    per_move: Option<Duration>,
+    per_game: Option<Duration>,
+}
+
+fn run_test(
+    opts: &Opts,
+    interactor: &Path,
+    time_limits: TimeLimits,
+    test: &Test,
+    test_dir: &Path,
+    game_log: &Path,
+) -> TestResult {
+    let answer = opts.problem_root.join(&test.answer);
+    std::fs::copy(&answer, test_dir.join("answer.txt"))
+        .unwrap_or_else(|error| panic!("The answer file {:?} cannot be copied: {}", answer, error));
+    // A game log or a result from the previous test must not be mistaken for the one of this test
+    std::fs::remove_file(game_log).ok();
+    let result_file = test_dir.join("result.txt");
+    std::fs::remove_file(&result_file).ok();
+
+    let stderr = || {
+        if opts.verbose {
+            Stdio::inherit()
+        } else {
+            Stdio::null()
+        }
+    };
+    let mut solution = Command::new(&opts.solution[0])
+        .args(&opts.solution[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(stderr())
+        .spawn()
+        .unwrap_or_else(|error| {
+            panic!(
+                "The solution {:?} cannot be started: {}",
+                opts.solution, error
+            )
+        });
+    let mut interactor = Command::new(interactor);
+    interactor
+        .current_dir(test_dir)
+        // The judge does not set the options of the interactor
+        .env_remove("TETRIS_LEVEL")
+        .env_remove("TETRIS_MOVE_TIME_LIMIT")
+        .env_remove("TETRIS_GAME_TIME_LIMIT")
+        .env_remove("TETRIS_MAX_LINE_LENGTH")
+        .env_remove("TETRIS_MAX_COMMANDS")
+        .env_remove("TETRIS_REPORT")
+        .env_remove("TETRIS_FEEDBACK")
+        .env_remove("TETRIS_SEQUENCE")
+        .env_remove("TETRIS_SEQUENCE_END")
+        .env("TETRIS_GAME_LOG", game_log)
+        .env("TETRIS_RESULT_FILE", &result_file);
+    if let Some(per_move) = time_limits.per_move {
+        interactor.env("TETRIS_MOVE_TIME_LIMIT", per_move.as_secs_f64().to_string());
+    }
+    if let Some(per_game) = time_limits.per_game {
+        interactor.env("TETRIS_GAME_TIME_LIMIT", per_game.as_secs_f64().to_string());
+    }
+    if let Some(ref feedback) = opts.feedback {
+        interactor.env("TETRIS_FEEDBACK", feedback);
+    }
+    if let Some(ref sequence) = opts.sequence {
+        interactor.env("TETRIS_SEQUENCE", sequence);
+    }
+    if let Some(ref sequence_end) = opts.sequence_end {
+        interactor.env("TETRIS_SEQUENCE_END", sequence_end);
+    }
+    let mut interactor = interactor
+        .stdin(Stdio::from(
+            solution.stdout.take().expect("the stdout is piped"),
+        ))
+        .stdout(Stdio::from(
+            solution.stdin.take().expect("the stdin is piped"),
+        ))
+        .stderr(stderr())
+        .spawn()
+        .unwrap_or_else(|error| panic!("The interactor cannot be started: {}", error));
+
+    let interactor_status = interactor.wait().expect("the interactor has been started");
+    // The game is over, so the solution has nothing else to do
+    solution.kill().ok();
+    let solution_status = solution.wait().expect("the solution has been started");
+    debug!(
+        "The interactor has exited with {}, and the solution has exited with {}",
+        interactor_status, solution_status
+    );
+    let result = std::fs::read_to_string(&result_file).unwrap_or_default();
+    TestResult::new(interactor_status.code(), &result)
+}
+
+/// The summary of the game and the comments that follow it at the end of the game log.
+fn read_summary(game_log: &Path) -> Vec<String> {
+    let game_log = std::fs::read_to_string(game_log).unwrap_or_default();
+    game_log
+        .lines()
+        .skip_while(|line| !line.is_empty())
+        .filter_map(|line| line.strip_prefix("# "))
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_test_result() {
+        for &(exit_code, result, test_result, points) in &[
+            (Some(0), "ok", TestResult::Verdict(Verdict::Ok), 10),
+            (
+                Some(1),
+                "wrong answer",
+                TestResult::Verdict(Verdict::WrongAnswer),
+                0,
+            ),
+            (
+                Some(1),
+                "time limit exceeded TimeLimitExceeded. Lines: 3",
+                TestResult::Verdict(Verdict::TimeLimitExceeded),
+                0,
+            ),
+            (
+                Some(61),
+                "points 45",
+                TestResult::Verdict(Verdict::Partial(45)),
+                4,
+            ),
+            (Some(5), "", TestResult::InteractorFailure(Some(5)), 0),
+            (None, "", TestResult::InteractorFailure(None), 0),
+        ] {
+            assert_eq!(TestResult::new(exit_code, result), test_result);
+            assert_eq!(test_result.points(10), points, "{}", test_result);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! The problem description of DDOTS (`Problem.xml`):
+//!
+//! ```xml
+//! <Problem TimeLimit="0.5" MemoryLimit="64" InteractorExe="interactor" TestCount="2">
+//! <Test Input="tests/00.in" Answer="tests/01.out" Points="10"/>
+//! <Test Input="tests/00.in" Answer="tests/02.out" Points="10"/>
+//! </Problem>
+//! ```
+//!
+//! Only the time limit, the answer files and the points of the tests matter for the tetris
+//! interactor (the input files are empty, since the interactor talks to the solution over the
+//! pipes).
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tetris_core::parse_seconds;
+
+#[derive(Debug, PartialEq, Eq, derive_more::Display)]
+pub enum ProblemError {
+    #[display(fmt = "invalid XML: {}", _0)]
+    InvalidXml(String),
+    #[display(fmt = "the root element is <{}> instead of <Problem>", _0)]
+    NotProblem(String),
+    #[display(fmt = "invalid time limit: {:?}", _0)]
+    InvalidTimeLimit(String),
+    #[display(fmt = "the test {} has no {} attribute", _0, _1)]
+    MissingAttribute(usize, &'static str),
+    #[display(fmt = "the test {} has an invalid {} attribute: {:?}", _0, _1, _2)]
+    InvalidAttribute(usize, &'static str, String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Test {
+    /// The answer file (relative to the problem root), which the interactor gets as `answer.txt`.
+    pub answer: PathBuf,
+    pub points: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// The time that the solution may think during a game.
+    pub time_limit: Option<Duration>,
+    pub tests: Vec<Test>,
+}
+
+impl std::str::FromStr for Problem {
+    type Err = ProblemError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document = roxmltree::Document::parse(s)
+            .map_err(|error| ProblemError::InvalidXml(error.to_string()))?;
+        let root = document.root_element();
+        if root.tag_name().name() != "Problem" {
+            return Err(ProblemError::NotProblem(root.tag_name().name().into()));
+        }
+
+        let time_limit = root
+            .attribute("TimeLimit")
+            .map(|time_limit| {
+                parse_seconds(time_limit)
+                    .map_err(|_| ProblemError::InvalidTimeLimit(time_limit.into()))
+            })
+            .transpose()?;
+        let tests = root
+            .children()
+            .filter(|node| node.has_tag_name("Test"))
+            .enumerate()
+            .map(|(index, node)| {
+                let number = index + 1;
+                let attribute = |name| {
+                    node.attribute(name)
+                        .ok_or(ProblemError::MissingAttribute(number, name))
+                };
+                let points = attribute("Points")?;
+                Ok(Test {
+                    answer: attribute("Answer")?.into(),
+                    points: points.parse().map_err(|_| {
+                        ProblemError::InvalidAttribute(number, "Points", points.into())
+                    })?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { time_limit, tests })
+    }
+}
+
+impl Problem {
+    pub fn total_points(&self) -> u32 {
+        self.tests.iter().map(|test| test.points).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem() {
+        let problem: Problem = include_str!("../../problem-level-9/Problem.xml")
+            .parse()
+            .unwrap();
+        assert_eq!(problem.time_limit, Some(Duration::from_millis(500)));
+        assert_eq!(problem.tests.len(), 10);
+        assert_eq!(
+            problem.tests[0],
+            Test {
+                answer: "tests/01.out".into(),
+                points: 10
+            }
+        );
+        assert_eq!(problem.total_points(), 100);
+    }
+
+    #[test]
+    fn test_invalid_problem() {
+        assert_eq!(
+            "<Tests/>".parse::<Problem>(),
+            Err(ProblemError::NotProblem("Tests".into()))
+        );
+        assert_eq!(
+            r#"<Problem><Test Answer="01.out"/></Problem>"#.parse::<Problem>(),
+            Err(ProblemError::MissingAttribute(1, "Points"))
+        );
+        assert_eq!(
+            r#"<Problem><Test Answer="01.out" Points="10"/><Test Answer="02.out" Points="ten"/></Problem>"#
+                .parse::<Problem>(),
+            Err(ProblemError::InvalidAttribute(2, "Points", "ten".into()))
+        );
+        assert_eq!(
+            r#"<Problem TimeLimit="-1"/>"#.parse::<Problem>(),
+            Err(ProblemError::InvalidTimeLimit("-1".into()))
+        );
+        assert!(matches!(
+            "<Problem></Test>".parse::<Problem>(),
+            Err(ProblemError::InvalidXml(_))
+        ));
+    }
+}
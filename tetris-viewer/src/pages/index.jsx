@@ -195,56 +195,99 @@ const TETROMINO_BLOCKS = {
   ],
 };
 
-const Block = ({ color, lineIndex, columnIndex, style }) => {
+const Block = ({ color, lineIndex, columnIndex, size, style }) => {
   return (
-    <>
-      <div
-        className="tetris-block"
-        style={{
-          backgroundColor: color,
-          position: "absolute",
-          top: lineIndex * 43,
-          left: columnIndex * 43,
-          ...style,
-        }}
-      ></div>
-      <style jsx>{`
-        .tetris-block {
-          width: 43px;
-          height: 43px;
-        }
-      `}</style>
-    </>
+    <div
+      style={{
+        backgroundColor: color,
+        position: "absolute",
+        top: lineIndex * size,
+        left: columnIndex * size,
+        width: size,
+        height: size,
+        ...style,
+      }}
+    ></div>
   );
 };
 
-function emptyLine() {
+// The board of 10x20 blocks of 43px fills the well of the background
+const BOARD_PIXEL_WIDTH = 430;
+const BOARD_PIXEL_HEIGHT = 860;
+
+const GARBAGE_COLOR = "dimgrey";
+
+function emptyLine(width) {
   const line = [];
-  for (let column = 0; column < 10; ++column) {
+  for (let column = 0; column < width; ++column) {
     line.push("");
   }
   return line;
 }
 
+function tetrominoBlocks(kind, rotation, columnIndex) {
+  return TETROMINO_BLOCKS[kind][rotation % 4].map(([x, y]) => [
+    columnIndex + x,
+    y,
+  ]);
+}
+
 class Tetris extends React.Component {
   state = {};
 
   init = (gameLog, callback) => {
+    let logs = gameLog.split("\n");
+    // The logs of the boards other than 10x20 start with their width and height
+    let width = 10;
+    let height = 20;
+    if (logs[0].startsWith("size ")) {
+      [width, height] = logs[0]
+        .split(" ")
+        .slice(1)
+        .map((value) => parseInt(value));
+      logs = logs.slice(1);
+    }
+    // The viewer only draws the classic rotation, so the other games would be drawn wrong
+    if (logs[0].startsWith("rotation ")) {
+      const rotation = logs[0].split(" ")[1];
+      if (rotation !== "classic") {
+        this.setState({
+          score: 0,
+          unsupported: `The ${rotation} rotation is not supported`,
+        });
+        return;
+      }
+      logs = logs.slice(1);
+    }
     let board = [];
-    for (let line = 0; line < 20; ++line) {
-      board.push(emptyLine());
+    for (let line = 0; line < height; ++line) {
+      board.push(emptyLine(width));
+    }
+    if (logs[0].startsWith("board ")) {
+      // The starting board of the test, with the rows from the top to the bottom
+      const rows = logs.slice(1, 1 + parseInt(logs[0].split(" ")[1]));
+      rows.reverse().forEach((row, lineIndex) => {
+        board[lineIndex] = emptyLine(width).map((_, column) =>
+          row[column + 1] === "#" ? GARBAGE_COLOR : ""
+        );
+      });
+      logs = logs.slice(1 + rows.length);
     }
     this.setState(
       {
+        width,
+        height,
+        unsupported: null,
         score: 0,
         persistentBoard: board.map((line) => line.slice()),
         board,
-        logs: gameLog.split("\n"),
+        logs,
         actions: [],
         tetrominoKind: "",
         tetrominoLineIndex: -1,
         tetrominoColumnIndex: 0,
         tetrominoRotation: 0,
+        heldTetrominoKind: "",
       },
       callback
     );
@@ -263,6 +306,8 @@ class Tetris extends React.Component {
   fit = (board, lineIndex, blocks) => {
     for (const block of blocks) {
       if (
+        block[0] < 0 ||
+        block[0] >= this.state.width ||
         block[1] > lineIndex ||
         board[lineIndex - block[1]][block[0]] !== ""
       ) {
@@ -272,12 +317,54 @@ class Tetris extends React.Component {
     return true;
   };
 
+  // Moves the tetromino unless it would leave the board or overlap with the stack
+  tryMove = (newState, tetrominoColumnIndex, tetrominoRotation) => {
+    const { tetrominoKind, tetrominoLineIndex } = this.state;
+    const blocks = tetrominoBlocks(
+      tetrominoKind,
+      tetrominoRotation,
+      tetrominoColumnIndex
+    );
+    if (this.fit(this.state.persistentBoard, tetrominoLineIndex, blocks)) {
+      newState.tetrominoColumnIndex = tetrominoColumnIndex;
+      newState.tetrominoRotation = tetrominoRotation;
+    }
+  };
+
   playNext = () => {
     console.log("NEXT", this.state);
     if (this.state.gameOver) {
       return;
     }
     const newState = {};
+    if (
+      this.state.tetrominoLineIndex === -1 &&
+      this.state.logs.length > 0 &&
+      this.state.logs[0].startsWith("garbage ")
+    ) {
+      // The garbage row rises from the bottom and pushes the stack up
+      const {
+        logs: [garbage, ...logs],
+      } = this.state;
+      const hole = parseInt(garbage.split(" ")[1]) - 1;
+      const garbageLine = emptyLine(this.state.width).map((_, column) =>
+        column === hole ? "" : GARBAGE_COLOR
+      );
+      const persistentBoard = this.state.persistentBoard.map((line) =>
+        line.slice()
+      );
+      const topLine = persistentBoard.pop();
+      persistentBoard.unshift(garbageLine);
+      newState.logs = logs;
+      newState.persistentBoard = persistentBoard;
+      newState.board = persistentBoard.map((line) => line.slice());
+      if (topLine.some((cell) => cell !== "")) {
+        newState.gameOver = true;
+      }
+      this.setState(newState);
+      setTimeout(this.playNext, 500 / this.props.speed);
+      return;
+    }
     if (this.state.tetrominoLineIndex === -1) {
       const {
         logs: [tetromino, actions, ...logs],
@@ -288,7 +375,7 @@ class Tetris extends React.Component {
       newState.logs = logs;
       const [tetrominoKind, tetrominoPosition] = tetromino.split(" ");
       newState.tetrominoKind = tetrominoKind;
-      newState.tetrominoLineIndex = 19;
+      newState.tetrominoLineIndex = this.state.height - 1;
       newState.tetrominoColumnIndex = parseInt(tetrominoPosition) - 1;
       newState.tetrominoRotation = 0;
       newState.actions = actions.split(" ");
@@ -302,14 +389,55 @@ class Tetris extends React.Component {
 
       switch (action) {
         case "shift_left":
-          newState.tetrominoColumnIndex = tetrominoColumnIndex - 1;
+          this.tryMove(newState, tetrominoColumnIndex - 1, tetrominoRotation);
           break;
         case "shift_right":
-          newState.tetrominoColumnIndex = tetrominoColumnIndex + 1;
+          this.tryMove(newState, tetrominoColumnIndex + 1, tetrominoRotation);
           break;
-        case "rotate":
-          newState.tetrominoRotation = tetrominoRotation + 1;
+        case "rotate": {
+          // The tetromino that sticks out of the right wall moves to the left
+          const rightmostColumnIndex = Math.max(
+            ...tetrominoBlocks(
+              this.state.tetrominoKind,
+              tetrominoRotation + 1,
+              0
+            ).map(([x, _]) => x)
+          );
+          this.tryMove(
+            newState,
+            Math.min(
+              tetrominoColumnIndex,
+              this.state.width - 1 - rightmostColumnIndex
+            ),
+            tetrominoRotation + 1
+          );
           break;
+        }
+        case "down": {
+          const { tetrominoKind, tetrominoLineIndex } = this.state;
+          const blocks = tetrominoBlocks(
+            tetrominoKind,
+            tetrominoRotation,
+            tetrominoColumnIndex
+          );
+          if (
+            this.fit(this.state.persistentBoard, tetrominoLineIndex - 1, blocks)
+          ) {
+            newState.tetrominoLineIndex = tetrominoLineIndex - 1;
+          } else {
+            // The tetromino has landed, so the rest of the actions are ignored
+            newState.actions = [];
+          }
+          break;
+        }
+        case "hold":
+          // The held tetromino does not fall, and the next one comes from the log
+          newState.heldTetrominoKind = this.state.tetrominoKind;
+          newState.tetrominoLineIndex = -1;
+          newState.board = this.state.persistentBoard.map((line) => line.slice());
+          this.setState(newState);
+          setTimeout(this.playNext, 2000 / this.props.speed);
+          return;
       }
     } else {
       let {
@@ -319,16 +447,19 @@ class Tetris extends React.Component {
         tetrominoRotation,
       } = this.state;
       --tetrominoLineIndex;
-      let blocks = TETROMINO_BLOCKS[tetrominoKind][
-        tetrominoRotation % 4
-      ].map(([x, y]) => [tetrominoColumnIndex + x, y]);
+      const blocks = tetrominoBlocks(
+        tetrominoKind,
+        tetrominoRotation,
+        tetrominoColumnIndex
+      );
       if (!this.fit(this.state.persistentBoard, tetrominoLineIndex, blocks)) {
         let { score } = this.state;
+        const { width, height } = this.state;
         const persistentBoard = this.state.board.map((line) => line.slice());
-        for (let lineIndex = 0; lineIndex < 19; ++lineIndex) {
+        for (let lineIndex = 0; lineIndex < height; ++lineIndex) {
           if (persistentBoard[lineIndex].every((cell) => cell !== "")) {
             persistentBoard.copyWithin(lineIndex, lineIndex + 1);
-            persistentBoard[19] = emptyLine();
+            persistentBoard[height - 1] = emptyLine(width);
             score += 1;
             --lineIndex;
           }
@@ -338,7 +469,7 @@ class Tetris extends React.Component {
         newState.tetrominoLineIndex = -1;
         if (
           persistentBoard
-            .slice(16, 19)
+            .slice(height - 4, height - 1)
             .some((line) => line.some((cell) => cell !== ""))
         ) {
           newState.gameOver = true;
@@ -356,13 +487,11 @@ class Tetris extends React.Component {
       tetrominoRotation,
     } = { ...this.state, ...newState };
 
-    let blocks = TETROMINO_BLOCKS[tetrominoKind][tetrominoRotation % 4];
-
-    blocks = blocks.map(([x, y]) => [tetrominoColumnIndex + x, y]);
-    if (!blocks.every(([x, _]) => x <= 9)) {
-      return;
-    }
-
+    const blocks = tetrominoBlocks(
+      tetrominoKind,
+      tetrominoRotation,
+      tetrominoColumnIndex
+    );
     const color = COLORS[tetrominoKind];
 
     const board = this.state.persistentBoard.map((line) => line.slice());
@@ -379,6 +508,15 @@ class Tetris extends React.Component {
     if (this.state.score === undefined) {
       return null;
     }
+    if (this.state.unsupported) {
+      return <p>{this.state.unsupported}</p>;
+    }
+    const blockSize = Math.floor(
+      Math.min(
+        BOARD_PIXEL_WIDTH / this.state.width,
+        BOARD_PIXEL_HEIGHT / this.state.height
+      )
+    );
     return (
       <>
         <div className="tetris-gameplay">
@@ -393,6 +531,9 @@ class Tetris extends React.Component {
             <div className="tetris-score">{this.state.score}</div>
             <div className="tetris-input">
               <p>{`Current: ${this.state.tetrominoKind}`}</p>
+              {this.state.heldTetrominoKind ? (
+                <p>{`Hold: ${this.state.heldTetrominoKind}`}</p>
+              ) : null}
               <p>{this.state.actions.join(" ")}</p>
             </div>
           </div>
@@ -408,6 +549,7 @@ class Tetris extends React.Component {
                       color={cell}
                       lineIndex={lineIndex}
                       columnIndex={columnIndex}
+                      size={blockSize}
                     />
                   );
                 });